@@ -6,12 +6,25 @@ struct Frame: Identifiable {
     let startTime: Date
     let endTime: Date?
     let tags: [String]
+    let breaks: [Break]
+
+    /// A pause within the frame; an open break means the frame is paused.
+    struct Break {
+        let start: Date
+        let end: Date?
+    }
 
     var isActive: Bool { endTime == nil }
 
+    var isPaused: Bool { breaks.contains { $0.end == nil } }
+
+    /// Time worked, excluding pauses.
     var duration: TimeInterval {
         let end = endTime ?? Date()
-        return end.timeIntervalSince(startTime)
+        let paused = breaks.reduce(0) { total, b in
+            total + max(0, min(b.end ?? end, end).timeIntervalSince(max(b.start, startTime)))
+        }
+        return end.timeIntervalSince(startTime) - paused
     }
 
     var formattedDuration: String {
@@ -2,6 +2,10 @@ import Foundation
 import SQLite
 
 final class TimerDatabase {
+    /// Highest `PRAGMA user_version` this app understands. Keep in step with
+    /// `SCHEMA_VERSION` in the CLI's src/db.rs.
+    static let supportedSchemaVersion: Int64 = 10
+
     private let db: Connection
 
     static let shared: TimerDatabase? = {
@@ -16,6 +20,11 @@ final class TimerDatabase {
     private init() throws {
         let path = try Self.databasePath()
         db = try Connection(path)
+
+        let version = try db.scalar("PRAGMA user_version") as? Int64 ?? 0
+        if version > Self.supportedSchemaVersion {
+            throw NSError(domain: "TimerDatabase", code: 2, userInfo: [NSLocalizedDescriptionKey: "Database schema version \(version) is newer than this app supports (\(Self.supportedSchemaVersion))"])
+        }
     }
 
     private static func databasePath() throws -> String {
@@ -32,7 +41,7 @@ final class TimerDatabase {
 
     func currentFrame() -> Frame? {
         let query = """
-            SELECT id, project, start_time, end_time, tags
+            SELECT id, project, start_time, end_time
             FROM frames WHERE end_time IS NULL LIMIT 1
         """
         do {
@@ -65,17 +74,43 @@ final class TimerDatabase {
     }
 
     func start(project: String, tags: [String] = []) throws {
-        let tagsStr = tags.isEmpty ? nil : tags.joined(separator: ",")
         let now = Int64(Date().timeIntervalSince1970)
-        try db.run(
-            "INSERT INTO frames (project, start_time, tags) VALUES (?, ?, ?)",
-            project, now, tagsStr
-        )
+        // IMMEDIATE matches the CLI, so the two can't both start a frame.
+        // The database also rejects a second open frame outright.
+        try db.transaction(.immediate) {
+            let running = try db.scalar("SELECT COUNT(*) FROM frames WHERE end_time IS NULL") as? Int64 ?? 0
+            if running > 0 {
+                throw NSError(domain: "TimerDatabase", code: 3, userInfo: [NSLocalizedDescriptionKey: "Already tracking - stop first"])
+            }
+            // uuid, created_at, updated_at and billable are filled in by triggers
+            try db.run(
+                "INSERT INTO frames (project, start_time) VALUES (?, ?)",
+                project, now
+            )
+            let frameId = db.lastInsertRowid
+            for (position, tag) in tags.enumerated() {
+                try db.run(
+                    "INSERT OR IGNORE INTO frame_tags (frame_id, tag, position) VALUES (?, ?, ?)",
+                    frameId, tag, Int64(position)
+                )
+            }
+        }
     }
 
     func stop() throws {
         let now = Int64(Date().timeIntervalSince1970)
-        try db.run("UPDATE frames SET end_time = ? WHERE end_time IS NULL", now)
+        try db.transaction {
+            // End any pause along with the frame, as the CLI does
+            try db.run(
+                """
+                UPDATE frame_breaks SET end_time = ?
+                WHERE end_time IS NULL
+                  AND frame_id IN (SELECT id FROM frames WHERE end_time IS NULL)
+                """,
+                now
+            )
+            try db.run("UPDATE frames SET end_time = ? WHERE end_time IS NULL", now)
+        }
     }
 
     private func parseFrame(_ row: Statement.Element) -> Frame? {
@@ -85,14 +120,50 @@ final class TimerDatabase {
             return nil
         }
         let endTs = row[3] as? Int64
-        let tagsStr = row[4] as? String
 
         return Frame(
             id: id,
             project: project,
             startTime: Date(timeIntervalSince1970: Double(startTs)),
             endTime: endTs.map { Date(timeIntervalSince1970: Double($0)) },
-            tags: tagsStr?.split(separator: ",").map(String.init) ?? []
+            tags: tags(frameId: id),
+            breaks: breaks(frameId: id)
         )
     }
+
+    private func breaks(frameId: Int64) -> [Frame.Break] {
+        var breaks: [Frame.Break] = []
+        do {
+            for row in try db.prepare(
+                "SELECT start_time, end_time FROM frame_breaks WHERE frame_id = ? ORDER BY start_time", frameId
+            ) {
+                if let startTs = row[0] as? Int64 {
+                    let endTs = row[1] as? Int64
+                    breaks.append(Frame.Break(
+                        start: Date(timeIntervalSince1970: Double(startTs)),
+                        end: endTs.map { Date(timeIntervalSince1970: Double($0)) }
+                    ))
+                }
+            }
+        } catch {
+            print("Query error: \(error)")
+        }
+        return breaks
+    }
+
+    private func tags(frameId: Int64) -> [String] {
+        var tags: [String] = []
+        do {
+            for row in try db.prepare(
+                "SELECT tag FROM frame_tags WHERE frame_id = ? ORDER BY position", frameId
+            ) {
+                if let tag = row[0] as? String {
+                    tags.append(tag)
+                }
+            }
+        } catch {
+            print("Query error: \(error)")
+        }
+        return tags
+    }
 }
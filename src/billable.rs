@@ -0,0 +1,30 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+
+/// Make new frames on `project` and its sub-projects billable or not.
+pub fn set(conn: &Connection, project: &str, billable: bool) -> Result<()> {
+    conn.execute(
+        "INSERT INTO project_billing (project, billable) VALUES (?1, ?2)
+         ON CONFLICT(project) DO UPDATE SET billable = excluded.billable",
+        params![project, billable],
+    )
+    .context("failed to save billable default")?;
+    Ok(())
+}
+
+/// Remove a project's default. Returns false if there was none.
+pub fn remove(conn: &Connection, project: &str) -> Result<bool> {
+    let changes = conn
+        .execute("DELETE FROM project_billing WHERE project = ?1", [project])
+        .context("failed to remove billable default")?;
+    Ok(changes > 0)
+}
+
+/// Every project with a default, by name.
+pub fn all(conn: &Connection) -> Result<Vec<(String, bool)>> {
+    let mut stmt = conn.prepare("SELECT project, billable FROM project_billing ORDER BY project")?;
+    let defaults = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(defaults)
+}
@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, Local};
+use rusqlite::types::Type;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::frame::{Filter, Frame, FrameQuery};
+use crate::project::SEPARATOR;
+use crate::time::{DateRange, Period};
+
+/// How often a budget resets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BudgetPeriod {
+    Day,
+    Week,
+    Month,
+    Year,
+    /// Never resets - counts all time
+    Total,
+}
+
+impl BudgetPeriod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetPeriod::Day => "day",
+            BudgetPeriod::Week => "week",
+            BudgetPeriod::Month => "month",
+            BudgetPeriod::Year => "year",
+            BudgetPeriod::Total => "total",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            BudgetPeriod::Day => "today",
+            BudgetPeriod::Week => "this week",
+            BudgetPeriod::Month => "this month",
+            BudgetPeriod::Year => "this year",
+            BudgetPeriod::Total => "total",
+        }
+    }
+
+    /// The current period, or `None` for all time.
+    fn range(self) -> Option<DateRange> {
+        let period = match self {
+            BudgetPeriod::Day => Period::Day,
+            BudgetPeriod::Week => Period::Week,
+            BudgetPeriod::Month => Period::Month,
+            BudgetPeriod::Year => Period::Year,
+            BudgetPeriod::Total => return None,
+        };
+        Some(DateRange::period(period, Local::now().date_naive()))
+    }
+}
+
+impl std::str::FromStr for BudgetPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "day" => Ok(BudgetPeriod::Day),
+            "week" => Ok(BudgetPeriod::Week),
+            "month" => Ok(BudgetPeriod::Month),
+            "year" => Ok(BudgetPeriod::Year),
+            "total" => Ok(BudgetPeriod::Total),
+            _ => Err(format!(
+                "unknown period: {s} (expected day, week, month, year or total)"
+            )),
+        }
+    }
+}
+
+pub struct Budget {
+    pub project: String,
+    pub limit: Duration,
+    pub period: BudgetPeriod,
+}
+
+/// Time tracked against a budget in its current period.
+pub struct Usage {
+    pub consumed: Duration,
+    pub limit: Duration,
+    pub period: BudgetPeriod,
+}
+
+impl Usage {
+    pub fn is_over(&self) -> bool {
+        self.consumed > self.limit
+    }
+
+    pub fn percent(&self) -> i64 {
+        if self.limit.is_zero() {
+            return 100;
+        }
+        self.consumed.num_seconds() * 100 / self.limit.num_seconds()
+    }
+
+    /// e.g. "32h 10m / 40h 0m this month (80%), 7h 50m left"
+    pub fn summary(&self) -> String {
+        let remaining = if self.is_over() {
+            format!("over by {}", Frame::format_duration(self.consumed - self.limit))
+        } else {
+            format!("{} left", Frame::format_duration(self.limit - self.consumed))
+        };
+        format!(
+            "{} / {} {} ({}%), {}",
+            Frame::format_duration(self.consumed),
+            Frame::format_duration(self.limit),
+            self.period.label(),
+            self.percent(),
+            remaining
+        )
+    }
+}
+
+pub fn set(conn: &Connection, project: &str, limit: Duration, period: BudgetPeriod) -> Result<()> {
+    conn.execute(
+        "INSERT INTO budgets (project, seconds, period) VALUES (?1, ?2, ?3)
+         ON CONFLICT(project) DO UPDATE SET seconds = excluded.seconds, period = excluded.period",
+        params![project, limit.num_seconds(), period.as_str()],
+    )
+    .context("failed to save budget")?;
+    Ok(())
+}
+
+/// Remove a project's budget. Returns false if it had none.
+pub fn remove(conn: &Connection, project: &str) -> Result<bool> {
+    let changes = conn
+        .execute("DELETE FROM budgets WHERE project = ?1", [project])
+        .context("failed to remove budget")?;
+    Ok(changes > 0)
+}
+
+pub fn get(conn: &Connection, project: &str) -> Result<Option<Budget>> {
+    conn.query_row(
+        "SELECT project, seconds, period FROM budgets WHERE project = ?1",
+        [project],
+        row_to_budget,
+    )
+    .optional()
+    .context("failed to query budget")
+}
+
+pub fn all(conn: &Connection) -> Result<Vec<Budget>> {
+    let mut stmt = conn.prepare("SELECT project, seconds, period FROM budgets ORDER BY project")?;
+    let budgets = stmt
+        .query_map([], row_to_budget)?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(budgets)
+}
+
+fn row_to_budget(row: &rusqlite::Row) -> rusqlite::Result<Budget> {
+    let period: String = row.get(2)?;
+    Ok(Budget {
+        project: row.get(0)?,
+        limit: Duration::seconds(row.get(1)?),
+        period: period
+            .parse()
+            .map_err(|e: String| rusqlite::Error::FromSqlConversionFailure(2, Type::Text, e.into()))?,
+    })
+}
+
+/// Time tracked on the budget's project and its sub-projects in the current
+/// period, excluding pauses.
+pub fn usage(conn: &Connection, budget: &Budget) -> Result<Usage> {
+    let range = budget.period.range();
+    let filter = Filter {
+        projects: vec![budget.project.clone()],
+        ..Default::default()
+    };
+    let consumed = FrameQuery::new()
+        .filter(filter)
+        .range(range)
+        .fetch(conn)?
+        .iter()
+        .map(|frame| match range {
+            Some(range) => {
+                let (from, to) = range.bounds();
+                frame.duration_between(from, to)
+            }
+            None => frame.duration(),
+        })
+        .sum();
+
+    Ok(Usage {
+        consumed,
+        limit: budget.limit,
+        period: budget.period,
+    })
+}
+
+/// Budgets that `project`'s time counts towards: its own and its parent
+/// projects', nearest first.
+pub fn covering(conn: &Connection, project: &str) -> Result<Vec<Budget>> {
+    let mut budgets = Vec::new();
+    let mut project = project;
+    loop {
+        budgets.extend(get(conn, project)?);
+        match project.rfind(SEPARATOR) {
+            Some(i) => project = &project[..i],
+            None => return Ok(budgets),
+        }
+    }
+}
+
+/// Usage for a project, if it has a budget.
+pub fn usage_for(conn: &Connection, project: &str) -> Result<Option<Usage>> {
+    get(conn, project)?
+        .map(|budget| usage(conn, &budget))
+        .transpose()
+}
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use chrono::Local;
+use rusqlite::{params, Connection};
+use std::fmt;
+
+/// Frames longer than this are reported unless `--max-hours` says otherwise.
+pub const DEFAULT_MAX_HOURS: i64 = 12;
+
+/// A data problem found in the frames table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Problem {
+    /// `other` starts while `id` is still running.
+    Overlap { id: i64, other: i64 },
+    /// End time is before start time.
+    Negative { id: i64 },
+    /// Duration exceeds `max_hours`.
+    TooLong { id: i64, max_hours: i64 },
+    /// An open frame that isn't the most recent one.
+    StrayOpen { id: i64 },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Overlap { id, other } => write!(f, "frame {} overlaps frame {}", id, other),
+            Problem::Negative { id } => write!(f, "frame {} ends before it starts", id),
+            Problem::TooLong { id, max_hours } => {
+                write!(f, "frame {} is longer than {}h", id, max_hours)
+            }
+            Problem::StrayOpen { id } => {
+                write!(f, "frame {} is still open but a later frame exists", id)
+            }
+        }
+    }
+}
+
+/// Scan every frame for problems.
+pub fn find_problems(conn: &Connection, max_hours: i64) -> Result<Vec<Problem>> {
+    let now = Local::now().timestamp();
+    let mut problems = Vec::new();
+
+    let mut stmt = conn.prepare(
+        "SELECT a.id, b.id
+         FROM frames a
+         JOIN frames b ON b.id <> a.id
+             AND b.start_time >= a.start_time
+             AND b.start_time < COALESCE(a.end_time, ?1)
+             AND NOT (b.start_time = a.start_time AND b.id < a.id)
+         WHERE COALESCE(a.end_time, ?1) >= a.start_time
+           AND COALESCE(b.end_time, ?1) >= b.start_time
+         ORDER BY a.start_time, b.start_time",
+    )?;
+    for row in stmt.query_map([now], |row| Ok((row.get(0)?, row.get(1)?)))? {
+        let (id, other) = row?;
+        problems.push(Problem::Overlap { id, other });
+    }
+
+    let mut stmt = conn.prepare("SELECT id FROM frames WHERE end_time < start_time ORDER BY start_time")?;
+    for id in stmt.query_map([], |row| row.get(0))? {
+        problems.push(Problem::Negative { id: id? });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id FROM frames WHERE COALESCE(end_time, ?1) - start_time > ?2 ORDER BY start_time",
+    )?;
+    for id in stmt.query_map(params![now, max_hours * 3600], |row| row.get(0))? {
+        problems.push(Problem::TooLong { id: id?, max_hours });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id FROM frames
+         WHERE end_time IS NULL
+           AND id <> (SELECT id FROM frames WHERE end_time IS NULL
+                      ORDER BY start_time DESC, id DESC LIMIT 1)
+         ORDER BY start_time",
+    )?;
+    for id in stmt.query_map([], |row| row.get(0))? {
+        problems.push(Problem::StrayOpen { id: id? });
+    }
+
+    Ok(problems)
+}
+
+/// Problems frame `id` would have if it ran from `start` to `end`.
+pub fn problems_with(
+    conn: &Connection,
+    id: i64,
+    start: i64,
+    end: Option<i64>,
+    max_hours: i64,
+) -> Result<Vec<Problem>> {
+    let now = Local::now().timestamp();
+    let effective_end = end.unwrap_or(now);
+    let mut problems = Vec::new();
+
+    if effective_end < start {
+        problems.push(Problem::Negative { id });
+        return Ok(problems);
+    }
+    if effective_end - start > max_hours * 3600 {
+        problems.push(Problem::TooLong { id, max_hours });
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id FROM frames
+         WHERE id <> ?1
+           AND start_time < ?3
+           AND COALESCE(end_time, ?4) > ?2
+         ORDER BY start_time",
+    )?;
+    for other in stmt.query_map(params![id, start, effective_end, now], |row| row.get(0))? {
+        problems.push(Problem::Overlap { id, other: other? });
+    }
+
+    Ok(problems)
+}
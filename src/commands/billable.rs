@@ -0,0 +1,40 @@
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::billable;
+
+fn label(billable: bool) -> &'static str {
+    if billable {
+        "billable"
+    } else {
+        "non-billable"
+    }
+}
+
+pub fn set(conn: &Connection, project: &str, billable: bool) -> Result<()> {
+    billable::set(conn, project, billable)?;
+    println!("New frames on {} are {}", project, label(billable));
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<()> {
+    let defaults = billable::all(conn)?;
+    if defaults.is_empty() {
+        println!("No billable defaults set (all projects are billable)");
+        return Ok(());
+    }
+
+    let max_project = defaults.iter().map(|(p, _)| p.len()).max().unwrap_or(0);
+    for (project, billable) in &defaults {
+        println!("{:<w$}  {}", project, label(*billable), w = max_project);
+    }
+    Ok(())
+}
+
+pub fn remove(conn: &Connection, project: &str) -> Result<()> {
+    if !billable::remove(conn, project)? {
+        anyhow::bail!("no billable default set for {}", project);
+    }
+    println!("Removed billable default for {}", project);
+    Ok(())
+}
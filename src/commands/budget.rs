@@ -0,0 +1,36 @@
+use anyhow::Result;
+use chrono::Duration;
+use rusqlite::Connection;
+
+use crate::budget::{self, BudgetPeriod};
+
+pub fn set(conn: &Connection, project: &str, limit: Duration, period: BudgetPeriod) -> Result<()> {
+    budget::set(conn, project, limit, period)?;
+    let usage = budget::usage_for(conn, project)?
+        .ok_or_else(|| anyhow::anyhow!("budget for {} was not saved", project))?;
+    println!("Budget for {}: {}", project, usage.summary());
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<()> {
+    let budgets = budget::all(conn)?;
+    if budgets.is_empty() {
+        println!("No budgets set");
+        return Ok(());
+    }
+
+    let max_project = budgets.iter().map(|b| b.project.len()).max().unwrap_or(0);
+    for b in &budgets {
+        let usage = budget::usage(conn, b)?;
+        println!("{:<w$}  {}", b.project, usage.summary(), w = max_project);
+    }
+    Ok(())
+}
+
+pub fn remove(conn: &Connection, project: &str) -> Result<()> {
+    if !budget::remove(conn, project)? {
+        anyhow::bail!("no budget set for {}", project);
+    }
+    println!("Removed budget for {}", project);
+    Ok(())
+}
@@ -2,18 +2,18 @@ use anyhow::Result;
 use rusqlite::Connection;
 
 use crate::frame;
+use crate::journal;
 
-pub fn run(conn: &Connection) -> Result<()> {
-    let current = frame::get_current(conn)?
+pub fn run(conn: &mut Connection) -> Result<()> {
+    let tx = conn.transaction()?;
+    let current = frame::get_current(&tx)?
         .ok_or_else(|| anyhow::anyhow!("not tracking"))?;
 
-    conn.execute("DELETE FROM frames WHERE id = ?1", [current.id])?;
+    let description = format!("cancel {}", journal::describe(&current));
+    journal::record(&tx, &description, std::slice::from_ref(&current))?;
+    tx.execute("DELETE FROM frames WHERE id = ?1", [current.id])?;
+    tx.commit()?;
 
-    let tags_str = if current.tags.is_empty() {
-        String::new()
-    } else {
-        format!(" +{}", current.tags.join(" +"))
-    };
-    println!("Cancelled {}{}", current.project, tags_str);
+    println!("Cancelled {}", current.label());
     Ok(())
 }
@@ -0,0 +1,143 @@
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection};
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+
+use crate::check::{self, Problem};
+use crate::frame::{timestamp_to_local, FrameQuery};
+use crate::journal::{self, Field};
+
+/// A proposed change to one frame.
+struct Fix {
+    id: i64,
+    start_time: i64,
+    end_time: i64,
+    description: String,
+}
+
+pub fn run(conn: &mut Connection, max_hours: i64, fix: bool) -> Result<()> {
+    if !fix {
+        let problems = check::find_problems(conn, max_hours)?;
+        if problems.is_empty() {
+            println!("No problems found");
+        }
+        for problem in &problems {
+            println!("{}", problem);
+        }
+        return Ok(());
+    }
+
+    // Fixing one problem can resolve or change others, so rescan after each
+    // fix and only skip the ones the user declined.
+    let mut skipped: HashSet<Problem> = HashSet::new();
+    let mut fixed = 0;
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        let problems = check::find_problems(conn, max_hours)?;
+        let Some(problem) = problems.into_iter().find(|p| !skipped.contains(p)) else {
+            break;
+        };
+
+        println!("{}", problem);
+        let Some(proposed) = propose_fix(conn, &problem)? else {
+            skipped.insert(problem);
+            continue;
+        };
+
+        print!("  {}? [y/N] ", proposed.description);
+        io::stdout().flush()?;
+        let answer = lines.next().transpose()?.unwrap_or_default();
+        if answer.trim().eq_ignore_ascii_case("y") {
+            let tx = conn.transaction()?;
+            let before = FrameQuery::new()
+                .id(proposed.id)
+                .fetch_one(&tx)?
+                .with_context(|| format!("frame {} not found", proposed.id))?;
+            let description = format!("fix {}", journal::describe(&before));
+            journal::record_fields(&tx, &description, &[before], &[Field::Times])?;
+            tx.execute(
+                "UPDATE frames SET start_time = ?1, end_time = ?2 WHERE id = ?3",
+                params![proposed.start_time, proposed.end_time, proposed.id],
+            )
+            .context("failed to apply fix")?;
+            tx.commit()?;
+            fixed += 1;
+        } else {
+            skipped.insert(problem);
+        }
+    }
+
+    println!("Fixed {} problem(s), {} left", fixed, skipped.len());
+    Ok(())
+}
+
+fn propose_fix(conn: &Connection, problem: &Problem) -> Result<Option<Fix>> {
+    let times = |id: i64| -> Result<(i64, Option<i64>)> {
+        conn.query_row(
+            "SELECT start_time, end_time FROM frames WHERE id = ?1",
+            [id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .with_context(|| format!("frame {} not found", id))
+    };
+    let hm = |ts: i64| timestamp_to_local(ts).format("%Y-%m-%d %H:%M").to_string();
+
+    let fix = match *problem {
+        Problem::Overlap { id, other } => {
+            let (start, _) = times(id)?;
+            let (end, _) = times(other)?;
+            if end <= start {
+                // Trimming would leave an empty frame
+                println!(
+                    "  Frames {} and {} start at the same time - edit or delete one of them",
+                    id, other
+                );
+                return Ok(None);
+            }
+            Some(Fix {
+                id,
+                start_time: start,
+                end_time: end,
+                description: format!("End frame {} at {} when frame {} starts", id, hm(end), other),
+            })
+        }
+        Problem::StrayOpen { id } => {
+            // End it where the next frame begins
+            let (start, _) = times(id)?;
+            let next_start: Option<i64> = conn.query_row(
+                "SELECT MIN(start_time) FROM frames WHERE start_time >= ?1 AND id <> ?2",
+                params![start, id],
+                |row| row.get(0),
+            )?;
+            next_start.map(|end| Fix {
+                id,
+                start_time: start,
+                end_time: end,
+                description: format!("End frame {} at {}", id, hm(end)),
+            })
+        }
+        Problem::Negative { id } => {
+            let (start, end) = times(id)?;
+            end.map(|end| Fix {
+                id,
+                start_time: end,
+                end_time: start,
+                description: format!("Swap start and end of frame {}", id),
+            })
+        }
+        Problem::TooLong { id, max_hours } => {
+            let (start, _) = times(id)?;
+            let end = start + max_hours * 3600;
+            Some(Fix {
+                id,
+                start_time: start,
+                end_time: end,
+                description: format!("End frame {} at {} ({}h)", id, hm(end), max_hours),
+            })
+        }
+    };
+
+    Ok(fix)
+}
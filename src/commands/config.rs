@@ -0,0 +1,66 @@
+use anyhow::{bail, Result};
+
+use crate::config;
+
+pub fn get(key: &str) -> Result<()> {
+    check_key(key)?;
+    match config::read_table()?.get(key) {
+        Some(toml::Value::String(s)) => println!("{}", s),
+        Some(value) => println!("{}", value),
+        None => bail!("{} is not set", key),
+    }
+    Ok(())
+}
+
+/// Set `key`. Values that parse as TOML (e.g. `true`) are stored as such,
+/// anything else as a string.
+pub fn set(key: &str, value: &str) -> Result<()> {
+    check_key(key)?;
+    let parsed = format!("v = {value}")
+        .parse::<toml::Table>()
+        .ok()
+        .and_then(|mut t| t.remove("v"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()));
+
+    // Only check the new value, so a bad setting elsewhere in the file isn't
+    // blamed on it
+    let single = toml::Table::from_iter([(key.to_string(), parsed.clone())]);
+    if config::validate(&single).is_err() {
+        let expected = config::KEYS.iter().find(|(k, _)| *k == key).map(|(_, d)| *d);
+        bail!("invalid value for {}: {} (expected {})", key, value, expected.unwrap_or("?"));
+    }
+    let mut table = config::read_table()?;
+    table.insert(key.to_string(), parsed);
+    config::write_table(&table)?;
+    println!("Set {} = {}", key, value);
+    Ok(())
+}
+
+/// Remove `key`. Unknown keys can be removed too, as long as they are present.
+pub fn unset(key: &str) -> Result<()> {
+    let mut table = config::read_table()?;
+    if table.remove(key).is_none() {
+        check_key(key)?;
+        bail!("{} is not set", key);
+    }
+    config::write_table(&table)?;
+    println!("Unset {}", key);
+    Ok(())
+}
+
+pub fn list() -> Result<()> {
+    let table = config::read_table()?;
+    if table.is_empty() {
+        println!("No settings in {}", config::path()?.display());
+        return Ok(());
+    }
+    print!("{}", toml::to_string(&table)?);
+    Ok(())
+}
+
+fn check_key(key: &str) -> Result<()> {
+    if !config::KEYS.iter().any(|(k, _)| *k == key) {
+        bail!("unknown setting '{}' (expected one of: {})", key, config::key_names());
+    }
+    Ok(())
+}
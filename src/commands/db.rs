@@ -0,0 +1,34 @@
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::db;
+
+pub fn migrate(conn: &mut Connection, dry_run: bool) -> Result<()> {
+    let pending = db::pending_migrations(conn)?;
+
+    if pending.is_empty() {
+        println!("Database is up to date (version {})", db::schema_version(conn)?);
+        return Ok(());
+    }
+
+    if dry_run {
+        for m in pending {
+            println!("Would apply {}: {}", m.version, m.description);
+        }
+        return Ok(());
+    }
+
+    for m in db::migrate(conn)? {
+        println!("Applied {}: {}", m.version, m.description);
+    }
+    Ok(())
+}
+
+pub fn version(conn: &Connection) -> Result<()> {
+    println!(
+        "Schema version {} (supported: {})",
+        db::schema_version(conn)?,
+        db::SCHEMA_VERSION
+    );
+    Ok(())
+}
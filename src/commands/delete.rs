@@ -1,13 +1,60 @@
 use anyhow::Result;
 use rusqlite::Connection;
 
-pub fn run(conn: &Connection, id: i64) -> Result<()> {
-    let changes = conn.execute("DELETE FROM frames WHERE id = ?1", [id])?;
+use crate::frame::{Filter, FrameQuery};
+use crate::journal;
+use crate::output;
+use crate::time::DateRange;
 
-    if changes == 0 {
+pub fn run(conn: &mut Connection, id: i64) -> Result<()> {
+    let tx = conn.transaction()?;
+    let Some(frame) = FrameQuery::new().id(id).fetch_one(&tx)? else {
         anyhow::bail!("frame {} not found", id);
+    };
+
+    journal::record(&tx, &format!("delete {}", journal::describe(&frame)), &[frame])?;
+    tx.execute("DELETE FROM frames WHERE id = ?1", [id])?;
+    tx.commit()?;
+
+    println!("Deleted frame {} (undo with `timer undo`)", id);
+    Ok(())
+}
+
+/// Delete every stopped frame starting in `range` that matches `filter` in
+/// one transaction, after showing them and asking unless `yes` is set. The
+/// running frame is left alone; `timer cancel` deletes that.
+pub fn run_matching(
+    conn: &mut Connection,
+    range: Option<DateRange>,
+    filter: Filter,
+    yes: bool,
+) -> Result<()> {
+    let tx = conn.transaction()?;
+    let mut query = FrameQuery::new().filter(filter);
+    if let Some(range) = range {
+        query = query.starts_in(range);
+    }
+    if query.clone().open(true).fetch_one(&tx)?.is_some() {
+        println!("Skipping the running frame (use `timer cancel` to delete it)");
+    }
+    let frames = query.open(false).fetch(&tx)?;
+    if frames.is_empty() {
+        println!("No frames found");
+        return Ok(());
+    }
+
+    output::print_preview(&frames);
+    if !yes && !output::confirm(&format!("Delete these {} frame(s)?", frames.len()))? {
+        println!("Nothing deleted");
+        return Ok(());
+    }
+
+    journal::record(&tx, &format!("delete {} frames", frames.len()), &frames)?;
+    for frame in &frames {
+        tx.execute("DELETE FROM frames WHERE id = ?1", [frame.id])?;
     }
+    tx.commit()?;
 
-    println!("Deleted frame {}", id);
+    println!("Deleted {} frame(s) (undo with `timer undo`)", frames.len());
     Ok(())
 }
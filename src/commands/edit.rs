@@ -1,68 +1,215 @@
-use anyhow::{Context, Result};
-use chrono::{Local, LocalResult, NaiveDateTime, TimeZone};
-use rusqlite::{Connection, OptionalExtension, params};
-
-pub fn run(
-    conn: &Connection,
-    id: i64,
-    project: Option<String>,
-    tags: Option<Vec<String>>,
-    start: Option<String>,
-    end: Option<String>,
-) -> Result<()> {
-    // Verify frame exists
-    let exists: bool = conn
-        .query_row("SELECT 1 FROM frames WHERE id = ?1", [id], |_| Ok(true))
-        .optional()
-        .context("failed to check if frame exists")?
-        .is_some();
-
-    if !exists {
-        anyhow::bail!("frame {} not found", id);
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection, Transaction};
+
+use crate::check;
+use crate::frame::{self, Filter, FrameQuery};
+use crate::journal::{self, Field};
+use crate::output;
+use crate::time::{parse_datetime, DateRange};
+
+/// Field changes requested by `timer edit`. `None` leaves a field as is.
+pub struct FrameEdit {
+    pub project: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub note: Option<String>,
+    pub billable: Option<bool>,
+}
+
+impl FrameEdit {
+    /// `project to acme, non-billable`, for the bulk edit prompt.
+    fn describe(&self) -> String {
+        let mut changes = Vec::new();
+        if let Some(ref project) = self.project {
+            changes.push(format!("project to {}", project));
+        }
+        if let Some(ref tags) = self.tags {
+            let tags: Vec<String> = tags.iter().map(|t| format!("+{}", t)).collect();
+            changes.push(format!("tags to {}", tags.join(" ")));
+        }
+        match self.note.as_deref() {
+            Some("") => changes.push("clear note".to_string()),
+            Some(note) => changes.push(format!("note to \"{}\"", note)),
+            None => {}
+        }
+        match self.billable {
+            Some(true) => changes.push("billable".to_string()),
+            Some(false) => changes.push("non-billable".to_string()),
+            None => {}
+        }
+        changes.join(", ")
     }
 
-    if let Some(ref proj) = project {
-        conn.execute("UPDATE frames SET project = ?1 WHERE id = ?2", params![proj, id])?;
+    /// The parts of a frame this edit changes, for the journal.
+    fn fields(&self) -> Vec<Field> {
+        let mut fields = Vec::new();
+        if self.project.is_some() {
+            fields.push(Field::Project);
+        }
+        if self.tags.is_some() {
+            fields.push(Field::Tags);
+        }
+        if self.start.is_some() || self.end.is_some() {
+            fields.push(Field::Times);
+        }
+        if self.note.is_some() {
+            fields.push(Field::Notes);
+        }
+        if self.billable.is_some() {
+            fields.push(Field::Billable);
+        }
+        fields
     }
 
-    if let Some(ref t) = tags {
-        let tags_str = if t.is_empty() { None } else { Some(t.join(",")) };
-        conn.execute("UPDATE frames SET tags = ?1 WHERE id = ?2", params![tags_str, id])?;
+    /// Apply the changes that don't involve times.
+    fn apply(&self, tx: &Transaction, id: i64) -> Result<()> {
+        if let Some(ref proj) = self.project {
+            tx.execute("UPDATE frames SET project = ?1 WHERE id = ?2", params![proj, id])?;
+        }
+
+        if let Some(ref t) = self.tags {
+            frame::set_tags(tx, id, t)?;
+        }
+
+        if let Some(ref n) = self.note {
+            // An empty note clears it
+            let n = if n.is_empty() { None } else { Some(n) };
+            tx.execute("UPDATE frames SET notes = ?1 WHERE id = ?2", params![n, id])?;
+        }
+
+        if let Some(billable) = self.billable {
+            frame::set_billable(tx, id, billable)?;
+        }
+        Ok(())
     }
+}
+
+pub fn run(conn: &mut Connection, id: i64, edit: FrameEdit, force: bool) -> Result<()> {
+    let tx = conn.transaction()?;
+    let Some(before) = FrameQuery::new().id(id).fetch_one(&tx)? else {
+        anyhow::bail!("frame {} not found", id);
+    };
+    let old_start = before.start_time.timestamp();
+    let old_end = before.end_time.map(|t| t.timestamp());
+
+    let start = edit
+        .start
+        .as_deref()
+        .map(|s| parse_datetime(s).context("invalid start time"))
+        .transpose()?
+        .map(|t| t.timestamp());
+    let end = edit
+        .end
+        .as_deref()
+        .map(|e| parse_datetime(e).context("invalid end time"))
+        .transpose()?
+        .map(|t| t.timestamp());
 
-    if let Some(ref s) = start {
-        let ts = parse_datetime(s).context("invalid start time")?;
-        conn.execute("UPDATE frames SET start_time = ?1 WHERE id = ?2", params![ts, id])?;
+    // Refuse to introduce overlaps or impossible times
+    if (start.is_some() || end.is_some()) && !force {
+        let problems = check::problems_with(
+            &tx,
+            id,
+            start.unwrap_or(old_start),
+            end.or(old_end),
+            check::DEFAULT_MAX_HOURS,
+        )?;
+        if !problems.is_empty() {
+            let list: Vec<String> = problems.iter().map(ToString::to_string).collect();
+            anyhow::bail!("{} (use --force to apply anyway)", list.join("; "));
+        }
     }
 
-    if let Some(ref e) = end {
-        let ts = parse_datetime(e).context("invalid end time")?;
-        conn.execute("UPDATE frames SET end_time = ?1 WHERE id = ?2", params![ts, id])?;
+    let description = format!("edit {}", journal::describe(&before));
+    journal::record_fields(&tx, &description, &[before], &edit.fields())?;
+
+    if let Some(ts) = start {
+        tx.execute("UPDATE frames SET start_time = ?1 WHERE id = ?2", params![ts, id])?;
+    }
+
+    match end {
+        // Ending a running frame stops it, closing any open pause
+        Some(ts) if old_end.is_none() => frame::stop(&tx, id, None, frame::timestamp_to_local(ts))?,
+        Some(ts) => {
+            tx.execute("UPDATE frames SET end_time = ?1 WHERE id = ?2", params![ts, id])?;
+        }
+        None => {}
     }
 
+    edit.apply(&tx, id)?;
+    tx.commit()?;
+
     println!("Updated frame {}", id);
     Ok(())
 }
 
-fn parse_datetime(s: &str) -> Result<i64> {
-    // Try formats: "HH:MM" (today), "YYYY-MM-DD HH:MM"
-    if let Ok(time) = chrono::NaiveTime::parse_from_str(s, "%H:%M") {
-        let today = Local::now().date_naive();
-        let dt = today.and_time(time);
-        return local_datetime_to_timestamp(&dt);
+/// Apply `edit` to every frame matching all `conditions` (`project`, `tag`,
+/// `from` or `to` = value) in one transaction, after showing them and asking
+/// unless `yes` is set. Conditions match like `delete`'s filters: `project`
+/// includes sub-projects, and `from`/`to` select frames starting in range.
+pub fn run_where(
+    conn: &mut Connection,
+    conditions: &[(String, String)],
+    edit: FrameEdit,
+    yes: bool,
+) -> Result<()> {
+    let changes = edit.describe();
+    if changes.is_empty() {
+        bail!("nothing to change (use --set-project, --tags, --note, --billable or --no-bill)");
+    }
+    let query = query_for(conditions)?;
+
+    let tx = conn.transaction()?;
+    let frames = query.fetch(&tx)?;
+    if frames.is_empty() {
+        println!("No frames found");
+        return Ok(());
+    }
+
+    output::print_preview(&frames);
+    if !yes && !output::confirm(&format!("Change {} on these {} frame(s)?", changes, frames.len()))? {
+        println!("Nothing changed");
+        return Ok(());
     }
 
-    if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M") {
-        return local_datetime_to_timestamp(&dt);
+    let description = format!("edit {} frames ({})", frames.len(), changes);
+    journal::record_fields(&tx, &description, &frames, &edit.fields())?;
+    for frame in &frames {
+        edit.apply(&tx, frame.id)?;
     }
+    tx.commit()?;
 
-    anyhow::bail!("expected HH:MM or YYYY-MM-DD HH:MM")
+    println!("Updated {} frame(s) (undo with `timer undo`)", frames.len());
+    Ok(())
 }
 
-fn local_datetime_to_timestamp(dt: &chrono::NaiveDateTime) -> Result<i64> {
-    match Local.from_local_datetime(dt) {
-        LocalResult::Single(local_dt) => Ok(local_dt.timestamp()),
-        LocalResult::Ambiguous(earliest, _) => Ok(earliest.timestamp()),
-        LocalResult::None => anyhow::bail!("invalid time (may be during DST transition)"),
+fn query_for(conditions: &[(String, String)]) -> Result<FrameQuery> {
+    let mut query = FrameQuery::new();
+    let (mut project, mut tag, mut from, mut to) = (None, None, None, None);
+    for (key, value) in conditions {
+        let slot = match key.as_str() {
+            "project" => &mut project,
+            "tag" => &mut tag,
+            "from" => &mut from,
+            "to" => &mut to,
+            _ => bail!("unknown condition '{key}' (expected project, tag, from or to)"),
+        };
+        if slot.replace(value.as_str()).is_some() {
+            bail!("{key} is given more than once");
+        }
+    }
+
+    let filter = Filter {
+        projects: project.map(String::from).into_iter().collect(),
+        tags: tag.map(|t| t.strip_prefix('+').unwrap_or(t).to_string()).into_iter().collect(),
+        ..Default::default()
+    };
+    query = query.filter(filter);
+    let from = from.map(DateRange::parse).transpose()?;
+    let to = to.map(DateRange::parse).transpose()?;
+    if from.is_some() || to.is_some() {
+        query = query.starts_in(DateRange::resolve(from, to, None));
     }
+    Ok(query)
 }
@@ -1,107 +1,57 @@
 use anyhow::Result;
-use chrono::Local;
 use rusqlite::Connection;
-use serde::Serialize;
 
-use crate::frame::timestamp_to_local;
-
-#[derive(Serialize)]
-struct ExportFrame {
-    id: i64,
-    project: String,
-    start_time: String,
-    end_time: Option<String>,
-    tags: Vec<String>,
-    duration_seconds: i64,
-}
-
-pub fn run(conn: &Connection, format: ExportFormat) -> Result<()> {
-    let frames = query_all_frames(conn)?;
-
-    match format {
-        ExportFormat::Json => {
-            let json = serde_json::to_string_pretty(&frames)?;
-            println!("{}", json);
-        }
-        ExportFormat::Csv => {
-            println!("id,project,start_time,end_time,tags,duration_seconds");
-            for f in frames {
-                println!(
-                    "{},{},{},{},{},{}",
-                    f.id,
-                    escape_csv(&f.project),
-                    f.start_time,
-                    f.end_time.unwrap_or_default(),
-                    escape_csv(&f.tags.join(",")),
-                    f.duration_seconds
-                );
+use crate::frame::{Filter, FrameQuery};
+use crate::output::{self, Format, FrameRecord};
+use crate::rounding::{RoundPer, Rounding};
+use crate::time::DateRange;
+
+/// Print frames as JSON (the default), CSV or TSV. With a rounding rule each
+/// frame also gets `rounded_seconds`; per-day rounding is refused since it
+/// can't be split back into frames.
+pub fn run(
+    conn: &Connection,
+    format: Option<Format>,
+    range: Option<DateRange>,
+    filter: Filter,
+    rounding: Option<Rounding>,
+) -> Result<()> {
+    if rounding.is_some_and(|r| r.per == RoundPer::Day) {
+        anyhow::bail!(
+            "export rounds each frame - pass --round-per frame, or use report for per-day rounding"
+        );
+    }
+    let frames = FrameQuery::new().range(range).filter(filter).fetch(conn)?;
+    let records: Vec<FrameRecord> = frames.iter().map(FrameRecord::from).collect();
+    let rounded: Option<Vec<i64>> = rounding
+        .map(|r| frames.iter().map(|f| r.round(f.duration()).num_seconds()).collect());
+
+    match format.unwrap_or(Format::Json) {
+        Format::Text => anyhow::bail!("export supports json, csv or tsv"),
+        Format::Json => match rounded {
+            Some(rounded) => {
+                let mut values = Vec::with_capacity(records.len());
+                for (record, seconds) in records.iter().zip(rounded) {
+                    let mut value = serde_json::to_value(record)?;
+                    value["rounded_seconds"] = seconds.into();
+                    values.push(value);
+                }
+                output::print_json(&values)?
             }
+            None => output::print_json(&records)?,
+        },
+        format => {
+            let mut header = FrameRecord::HEADER.to_vec();
+            let mut rows: Vec<_> = records.iter().map(FrameRecord::to_row).collect();
+            if let Some(rounded) = rounded {
+                header.push("rounded_seconds");
+                for (row, seconds) in rows.iter_mut().zip(rounded) {
+                    row.push(seconds.to_string());
+                }
+            }
+            output::print_table(format, &header, &rows);
         }
     }
 
     Ok(())
 }
-
-fn escape_csv(s: &str) -> String {
-    if s.contains(',') || s.contains('"') || s.contains('\n') {
-        format!("\"{}\"", s.replace('"', "\"\""))
-    } else {
-        s.to_string()
-    }
-}
-
-fn query_all_frames(conn: &Connection) -> Result<Vec<ExportFrame>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project, start_time, end_time, tags FROM frames ORDER BY start_time",
-    )?;
-
-    let now = Local::now();
-    let frames = stmt
-        .query_map([], |row| {
-            let id: i64 = row.get(0)?;
-            let project: String = row.get(1)?;
-            let start_ts: i64 = row.get(2)?;
-            let end_ts: Option<i64> = row.get(3)?;
-            let tags_str: Option<String> = row.get(4)?;
-
-            let start_time = timestamp_to_local(start_ts);
-            let end_time = end_ts.map(timestamp_to_local);
-
-            let duration_seconds = end_time
-                .unwrap_or(now)
-                .signed_duration_since(start_time)
-                .num_seconds();
-
-            Ok(ExportFrame {
-                id,
-                project,
-                start_time: start_time.format("%Y-%m-%dT%H:%M:%S").to_string(),
-                end_time: end_time.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
-                tags: tags_str
-                    .map(|s| s.split(',').map(String::from).collect())
-                    .unwrap_or_default(),
-                duration_seconds,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
-
-    Ok(frames)
-}
-
-#[derive(Clone, Copy)]
-pub enum ExportFormat {
-    Json,
-    Csv,
-}
-
-impl std::str::FromStr for ExportFormat {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "json" => Ok(ExportFormat::Json),
-            "csv" => Ok(ExportFormat::Csv),
-            _ => Err(format!("unknown format: {s} (expected json or csv)")),
-        }
-    }
-}
@@ -0,0 +1,106 @@
+use anyhow::{Context, Result};
+use rusqlite::{Connection, TransactionBehavior};
+use std::fs;
+use std::path::Path;
+
+use crate::frame::{self, FrameQuery};
+use crate::import::{self, ImportFormat};
+use crate::journal;
+
+/// Import finished frames from `path` in one transaction. A frame whose UUID
+/// is already in the database replaces it if the import has a newer
+/// `updated_at`; frames without a known UUID are skipped if one with the same
+/// project and times exists. Replaced frames are journaled for `undo`. With
+/// `dry_run` the transaction is rolled back.
+pub fn run(
+    conn: &mut Connection,
+    path: &Path,
+    format: Option<ImportFormat>,
+    dry_run: bool,
+) -> Result<()> {
+    let text =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let format = match format {
+        Some(format) => format,
+        None => ImportFormat::detect(&text)?,
+    };
+    let frames = import::parse(format, &text)
+        .with_context(|| format!("failed to import {}", path.display()))?;
+
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let (mut imported, mut duplicates, mut running) = (0, 0, 0);
+    let mut replaced = Vec::new();
+    for f in &frames {
+        let Some(end) = f.end_time else {
+            running += 1;
+            continue;
+        };
+        let existing = match &f.uuid {
+            Some(uuid) => FrameQuery::new().uuid(uuid).fetch_one(&tx)?,
+            None => None,
+        };
+        if let Some(existing) = existing {
+            if f.updated_at.is_none_or(|t| t <= existing.updated_at) {
+                duplicates += 1;
+                continue;
+            }
+            frame::replace_stopped(
+                &tx,
+                existing.id,
+                &f.project,
+                &f.tags,
+                f.notes.as_deref(),
+                f.start_time,
+                end,
+                f.paused,
+            )?;
+            if let Some(billable) = f.billable {
+                frame::set_billable(&tx, existing.id, billable)?;
+            }
+            frame::set_metadata(&tx, existing.id, None, None, f.updated_at)?;
+            replaced.push(existing);
+            continue;
+        }
+        if frame::exists(&tx, &f.project, f.start_time, end)? {
+            duplicates += 1;
+            continue;
+        }
+        let id = frame::insert_stopped(
+            &tx,
+            &f.project,
+            &f.tags,
+            f.notes.as_deref(),
+            f.start_time,
+            end,
+            f.paused,
+        )?;
+        if let Some(billable) = f.billable {
+            frame::set_billable(&tx, id, billable)?;
+        }
+        frame::set_metadata(&tx, id, f.uuid.as_deref(), f.created_at, f.updated_at)?;
+        imported += 1;
+    }
+
+    if !replaced.is_empty() {
+        let description = format!("import over {} frames from {}", replaced.len(), path.display());
+        journal::record(&tx, &description, &replaced)?;
+    }
+
+    let mut summary = if dry_run {
+        format!("Would import {} frame(s)", imported)
+    } else {
+        tx.commit()?;
+        format!("Imported {} frame(s)", imported)
+    };
+    if !replaced.is_empty() {
+        let verb = if dry_run { "update" } else { "updated" };
+        summary.push_str(&format!(", {} {} older frame(s)", verb, replaced.len()));
+    }
+    let verb = if dry_run { "skip" } else { "skipped" };
+    summary.push_str(&format!(", {} {} duplicate(s)", verb, duplicates));
+    if running > 0 {
+        summary.push_str(&format!(" and {} running frame(s)", running));
+    }
+    println!("{}", summary);
+    Ok(())
+}
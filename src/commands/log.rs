@@ -1,8 +1,9 @@
 use anyhow::Result;
-use chrono::{Local, LocalResult, NaiveDate, TimeZone};
 use rusqlite::Connection;
 
-use crate::frame::{timestamp_to_local, Frame};
+use crate::frame::{Filter, Frame, FrameQuery, Order};
+use crate::output::{self, Format, FrameRecord};
+use crate::time::DateRange;
 
 struct DisplayRow {
     id: String,
@@ -10,6 +11,7 @@ struct DisplayRow {
     tags: String,
     duration: String,
     time_range: String,
+    notes: String,
 }
 
 impl DisplayRow {
@@ -33,21 +35,34 @@ impl DisplayRow {
             tags,
             duration: Frame::format_duration(frame.duration()),
             time_range,
+            notes: frame.notes.clone().unwrap_or_default(),
         }
     }
 }
 
-pub fn run(conn: &Connection, from: Option<NaiveDate>, to: Option<NaiveDate>, all: bool) -> Result<()> {
-    let frames = if all {
-        query_all_frames(conn)?
-    } else {
-        let today = Local::now().date_naive();
-        let from_date = from.unwrap_or(today);
-        let to_date = to.unwrap_or(today);
-        let from_ts = date_to_start_timestamp(from_date);
-        let to_ts = date_to_end_timestamp(to_date);
-        query_frames(conn, from_ts, to_ts)?
-    };
+pub fn run(
+    conn: &Connection,
+    range: Option<DateRange>,
+    filter: Filter,
+    show_notes: bool,
+    format: Format,
+) -> Result<()> {
+    let frames = FrameQuery::new()
+        .range(range)
+        .filter(filter)
+        .order(Order::StartDesc)
+        .fetch(conn)?;
+
+    if format != Format::Text {
+        let records: Vec<FrameRecord> = frames.iter().map(FrameRecord::from).collect();
+        if format == Format::Json {
+            output::print_json(&records)?;
+        } else {
+            let rows: Vec<_> = records.iter().map(FrameRecord::to_row).collect();
+            output::print_table(format, FrameRecord::HEADER, &rows);
+        }
+        return Ok(());
+    }
 
     if frames.is_empty() {
         println!("No frames found");
@@ -63,7 +78,7 @@ pub fn run(conn: &Connection, from: Option<NaiveDate>, to: Option<NaiveDate>, al
     let max_duration = rows.iter().map(|r| r.duration.len()).max().unwrap_or(0);
 
     for row in &rows {
-        println!(
+        let line = format!(
             "[{:>id_w$}] {:<proj_w$}  {:<tag_w$}  {:>dur_w$}  {}",
             row.id, row.project, row.tags, row.duration, row.time_range,
             id_w = max_id,
@@ -71,72 +86,12 @@ pub fn run(conn: &Connection, from: Option<NaiveDate>, to: Option<NaiveDate>, al
             tag_w = max_tags,
             dur_w = max_duration,
         );
+        if show_notes && !row.notes.is_empty() {
+            println!("{}  {}", line, row.notes);
+        } else {
+            println!("{}", line);
+        }
     }
 
     Ok(())
 }
-
-fn date_to_start_timestamp(date: NaiveDate) -> i64 {
-    let dt = date.and_hms_opt(0, 0, 0).unwrap();
-    match Local.from_local_datetime(&dt) {
-        LocalResult::Single(local_dt) => local_dt.timestamp(),
-        LocalResult::Ambiguous(earliest, _) => earliest.timestamp(),
-        LocalResult::None => Local::now().timestamp(), // fallback
-    }
-}
-
-fn date_to_end_timestamp(date: NaiveDate) -> i64 {
-    let dt = date.and_hms_opt(23, 59, 59).unwrap();
-    match Local.from_local_datetime(&dt) {
-        LocalResult::Single(local_dt) => local_dt.timestamp(),
-        LocalResult::Ambiguous(_, latest) => latest.timestamp(),
-        LocalResult::None => Local::now().timestamp(), // fallback
-    }
-}
-
-fn query_frames(conn: &Connection, from_ts: i64, to_ts: i64) -> Result<Vec<Frame>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project, start_time, end_time, tags
-         FROM frames
-         WHERE start_time >= ?1 AND start_time <= ?2
-         ORDER BY start_time DESC",
-    )?;
-
-    let frames = stmt
-        .query_map([from_ts, to_ts], |row| row_to_frame(row))?
-        .collect::<Result<Vec<_>, _>>()?;
-
-    Ok(frames)
-}
-
-fn query_all_frames(conn: &Connection) -> Result<Vec<Frame>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project, start_time, end_time, tags
-         FROM frames
-         ORDER BY start_time DESC",
-    )?;
-
-    let frames = stmt
-        .query_map([], |row| row_to_frame(row))?
-        .collect::<Result<Vec<_>, _>>()?;
-
-    Ok(frames)
-}
-
-fn row_to_frame(row: &rusqlite::Row) -> rusqlite::Result<Frame> {
-    let id: i64 = row.get(0)?;
-    let project: String = row.get(1)?;
-    let start_ts: i64 = row.get(2)?;
-    let end_ts: Option<i64> = row.get(3)?;
-    let tags_str: Option<String> = row.get(4)?;
-
-    Ok(Frame {
-        id,
-        project,
-        start_time: timestamp_to_local(start_ts),
-        end_time: end_ts.map(timestamp_to_local),
-        tags: tags_str
-            .map(|s| s.split(',').map(String::from).collect())
-            .unwrap_or_default(),
-    })
-}
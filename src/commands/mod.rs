@@ -1,29 +1,49 @@
+mod billable;
+mod budget;
 mod cancel;
+mod check;
 mod completions;
+mod config;
+mod db;
 mod delete;
 mod edit;
 mod export;
+mod import;
 mod log;
+mod pause;
+mod rate;
 mod projects;
 mod report;
 mod restart;
+mod resume;
 mod start;
 mod status;
 mod stop;
 mod switch;
 mod tags;
+mod undo;
 
+pub use billable::{list as billable_list, remove as billable_remove, set as billable_set};
+pub use budget::{list as budget_list, remove as budget_remove, set as budget_set};
 pub use cancel::run as cancel;
+pub use check::run as check;
 pub use completions::run as completions;
-pub use delete::run as delete;
-pub use edit::run as edit;
-pub use export::{run as export, ExportFormat};
+pub use config::{get as config_get, list as config_list, set as config_set, unset as config_unset};
+pub use db::{migrate as db_migrate, version as db_version};
+pub use delete::{run as delete, run_matching as delete_matching};
+pub use edit::{run as edit, run_where as edit_where, FrameEdit};
+pub use export::run as export;
+pub use import::run as import;
 pub use log::run as log;
+pub use pause::run as pause;
+pub use rate::{list as rate_list, remove as rate_remove, set as rate_set};
 pub use projects::run as projects;
-pub use report::run as report;
+pub use report::{run as report, ReportOptions};
 pub use restart::run as restart;
+pub use resume::run as resume;
 pub use start::run as start;
 pub use status::run as status;
 pub use stop::run as stop;
 pub use switch::run as switch;
 pub use tags::run as tags;
+pub use undo::{list as undo_list, run as undo};
@@ -0,0 +1,23 @@
+use anyhow::{Result, bail};
+use chrono::Local;
+use rusqlite::Connection;
+
+use crate::frame::{self, Frame};
+
+pub fn run(conn: &Connection) -> Result<()> {
+    let frame = frame::get_current(conn)?
+        .ok_or_else(|| anyhow::anyhow!("not tracking"))?;
+
+    if frame.paused_since().is_some() {
+        bail!("already paused");
+    }
+
+    frame::pause(conn, frame.id, Local::now())?;
+
+    println!(
+        "Paused {} ({})",
+        frame.label(),
+        Frame::format_duration(frame.duration())
+    );
+    Ok(())
+}
@@ -1,7 +1,10 @@
 use anyhow::Result;
 use rusqlite::Connection;
 
-pub fn run(conn: &Connection) -> Result<()> {
+use crate::output::{self, Format};
+use crate::project::ProjectTree;
+
+pub fn run(conn: &Connection, tree: bool, format: Format) -> Result<()> {
     let mut stmt = conn.prepare(
         "SELECT DISTINCT project FROM frames ORDER BY project",
     )?;
@@ -10,8 +13,19 @@ pub fn run(conn: &Connection) -> Result<()> {
         .query_map([], |row| row.get(0))?
         .collect::<Result<Vec<_>, _>>()?;
 
-    if projects.is_empty() {
+    if format == Format::Json {
+        output::print_json(&projects)?;
+    } else if format != Format::Text {
+        let rows: Vec<_> = projects.into_iter().map(|p| vec![p]).collect();
+        output::print_table(format, &["project"], &rows);
+    } else if projects.is_empty() {
         println!("No projects found");
+    } else if tree {
+        let mut root = ProjectTree::default();
+        for project in &projects {
+            root.add(project, chrono::Duration::zero());
+        }
+        print_tree(&root, 0);
     } else {
         for project in projects {
             println!("{}", project);
@@ -20,3 +34,10 @@ pub fn run(conn: &Connection) -> Result<()> {
 
     Ok(())
 }
+
+fn print_tree(tree: &ProjectTree, depth: usize) {
+    for (name, node) in &tree.children {
+        println!("{}{}", "  ".repeat(depth), name);
+        print_tree(node, depth + 1);
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::Result;
+use rusqlite::Connection;
+
+use crate::rate::{self, Rate, RateTarget};
+
+pub fn set(conn: &Connection, target: RateTarget, amount: &str, currency: &str) -> Result<()> {
+    let rate = Rate {
+        target,
+        cents: rate::parse_amount(amount)?,
+        currency: rate::parse_currency(currency)?,
+    };
+    rate::set(conn, &rate)?;
+    println!(
+        "Rate for {}: {} {}/h",
+        rate.target,
+        rate::format_amount(rate.cents),
+        rate.currency
+    );
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<()> {
+    let rates = rate::all(conn)?;
+    if rates.is_empty() {
+        println!("No rates set");
+        return Ok(());
+    }
+
+    let targets: Vec<String> = rates.iter().map(|r| r.target.to_string()).collect();
+    let max_target = targets.iter().map(String::len).max().unwrap_or(0);
+    for (target, r) in targets.iter().zip(&rates) {
+        println!(
+            "{:<w$}  {} {}/h",
+            target,
+            rate::format_amount(r.cents),
+            r.currency,
+            w = max_target
+        );
+    }
+    Ok(())
+}
+
+pub fn remove(conn: &Connection, target: RateTarget) -> Result<()> {
+    if !rate::remove(conn, &target)? {
+        anyhow::bail!("no rate set for {}", target);
+    }
+    println!("Removed rate for {}", target);
+    Ok(())
+}
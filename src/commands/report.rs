@@ -1,143 +1,673 @@
 use anyhow::Result;
-use chrono::{Duration, Local, LocalResult, NaiveDate, TimeZone};
+use chrono::{Duration, NaiveDate};
 use rusqlite::Connection;
-use std::collections::HashMap;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::frame::{timestamp_to_local, Frame};
+use crate::budget;
+use crate::frame::{Filter, Frame, FrameQuery};
+use crate::grid::Grid;
+use crate::output::{self, Format};
+use crate::project::{ProjectTree, SEPARATOR};
+use crate::rate::{self, Rate, RateTarget, Rates};
+use crate::rounding::{self, Billed, Rounding};
+use crate::time::{DateRange, Period};
+
+/// How `report` groups and prints its totals.
+pub struct ReportOptions {
+    pub by_tag: bool,
+    pub by_day: bool,
+    /// Print a projects-by-period timesheet instead of totals
+    pub grid: Option<Period>,
+    /// Multiply time by project and tag rates
+    pub earnings: bool,
+    /// Round time in project, tag and earnings reports
+    pub rounding: Option<Rounding>,
+    pub format: Format,
+}
 
 pub fn run(
     conn: &Connection,
-    from: Option<NaiveDate>,
-    to: Option<NaiveDate>,
-    by_tag: bool,
-    all: bool,
+    range: Option<DateRange>,
+    filter: Filter,
+    options: ReportOptions,
 ) -> Result<()> {
-    let frames: Vec<Frame> = if all {
-        query_all_frames(conn)?
-    } else {
-        let today = Local::now().date_naive();
-        let from_date = from.unwrap_or(today);
-        let to_date = to.unwrap_or(today);
-        let from_ts = date_to_start_timestamp(from_date);
-        let to_ts = date_to_end_timestamp(to_date);
-        query_frames(conn, from_ts, to_ts)?
-    };
+    let frames = FrameQuery::new().range(range).filter(filter).fetch(conn)?;
+    let format = options.format;
+
+    if let Some(period) = options.grid {
+        return report_grid(&Grid::build(&frames, range, period), format);
+    }
 
-    if frames.is_empty() {
+    if frames.is_empty() && format == Format::Text {
         println!("No frames found");
         return Ok(());
     }
 
-    if by_tag {
-        print_by_tag(&frames);
+    let rounding = options.rounding.as_ref();
+    if options.earnings {
+        report_earnings(conn, &frames, range, rounding, format)
+    } else if options.by_tag {
+        report_by_tag(&frames, range, rounding, format)
+    } else if options.by_day {
+        report_by_day(&frames, range, format)
     } else {
-        print_by_project(&frames);
+        report_by_project(conn, &frames, range, rounding, format)
     }
-
-    Ok(())
 }
 
-fn date_to_start_timestamp(date: NaiveDate) -> i64 {
-    let dt = date.and_hms_opt(0, 0, 0).unwrap();
-    match Local.from_local_datetime(&dt) {
-        LocalResult::Single(local_dt) => local_dt.timestamp(),
-        LocalResult::Ambiguous(earliest, _) => earliest.timestamp(),
-        LocalResult::None => Local::now().timestamp(),
+/// `1h 5m`, followed by the rounded time when there is a rounding rule and
+/// the billable split when `billing` is set.
+fn with_details(billed: &Billed, rounding: Option<&Rounding>, billing: bool) -> String {
+    let mut details = Vec::new();
+    if rounding.is_some() {
+        details.push(format!("rounded: {}", Frame::format_duration(billed.rounded)));
+    }
+    if billing {
+        details.push(format!("billable: {}", Frame::format_duration(billed.billable)));
+        details.push(format!("non-billable: {}", Frame::format_duration(billed.non_billable())));
+    }
+    let raw = Frame::format_duration(billed.raw);
+    if details.is_empty() {
+        raw
+    } else {
+        format!("{} ({})", raw, details.join(", "))
     }
 }
 
-fn date_to_end_timestamp(date: NaiveDate) -> i64 {
-    let dt = date.and_hms_opt(23, 59, 59).unwrap();
-    match Local.from_local_datetime(&dt) {
-        LocalResult::Single(local_dt) => local_dt.timestamp(),
-        LocalResult::Ambiguous(_, latest) => latest.timestamp(),
-        LocalResult::None => Local::now().timestamp(),
+fn print_heading(heading: &str, rounding: Option<&Rounding>) {
+    match rounding {
+        Some(r) => println!("{} (rounded {}):", heading, r.describe()),
+        None => println!("{}:", heading),
     }
 }
 
-fn query_frames(conn: &Connection, from_ts: i64, to_ts: i64) -> Result<Vec<Frame>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project, start_time, end_time, tags
-         FROM frames
-         WHERE start_time >= ?1 AND start_time <= ?2",
-    )?;
+/// Add `billable_seconds` and `non_billable_seconds` to a report entry.
+fn add_billable_json(entry: &mut serde_json::Value, billed: &Billed, prefix: &str) {
+    entry[format!("{prefix}billable_seconds")] = billed.billable.num_seconds().into();
+    entry[format!("{prefix}non_billable_seconds")] = billed.non_billable().num_seconds().into();
+}
 
-    let frames = stmt
-        .query_map([from_ts, to_ts], |row| row_to_frame(row))?
-        .collect::<Result<Vec<_>, _>>()?;
+/// Totals per project, with `/`-separated sub-projects nested under their
+/// parent and counted in its subtotal. JSON and tables list each project
+/// on its own. Text only shows the billable split if some time isn't.
+fn report_by_project(
+    conn: &Connection,
+    frames: &[Frame],
+    range: Option<DateRange>,
+    rounding: Option<&Rounding>,
+    format: Format,
+) -> Result<()> {
+    let totals = rounding::billed(frames, range, rounding, |f| vec![f.project.as_str()]);
+    let mut tree = ProjectTree::default();
+    let mut total = Billed::default();
+    for (project, billed) in &totals {
+        tree.add(project, billed.raw);
+        total += *billed;
+    }
+    let sorted = by_duration(totals.iter().map(|(p, b)| (*p, b.raw)).collect());
 
-    Ok(frames)
+    match format {
+        Format::Text => {
+            let billing = frames.iter().any(|f| !f.billable);
+            print_heading("By project", rounding);
+            print_tree(&tree, &totals, "", 1, &|b| with_details(b, rounding, billing));
+            println!("Total: {}", with_details(&total, rounding, billing));
+            print_budgets(conn, frames)?;
+        }
+        Format::Json => {
+            let projects: Vec<_> = sorted
+                .iter()
+                .map(|(project, d)| {
+                    let mut entry = json!({ "project": project, "seconds": d.num_seconds() });
+                    if rounding.is_some() {
+                        entry["rounded_seconds"] = totals[project].rounded.num_seconds().into();
+                    }
+                    add_billable_json(&mut entry, &totals[project], "");
+                    entry
+                })
+                .collect();
+            let mut report = json!({
+                "projects": projects,
+                "total_seconds": total.raw.num_seconds(),
+            });
+            if rounding.is_some() {
+                report["total_rounded_seconds"] = total.rounded.num_seconds().into();
+            }
+            add_billable_json(&mut report, &total, "total_");
+            output::print_json(&report)?;
+        }
+        _ => {
+            let mut header = vec!["project", "seconds"];
+            if rounding.is_some() {
+                header.push("rounded_seconds");
+            }
+            header.extend(["billable_seconds", "non_billable_seconds"]);
+            let rows: Vec<_> = sorted
+                .iter()
+                .map(|(project, d)| {
+                    let billed = &totals[project];
+                    let mut row = vec![project.to_string(), d.num_seconds().to_string()];
+                    if rounding.is_some() {
+                        row.push(billed.rounded.num_seconds().to_string());
+                    }
+                    row.push(billed.billable.num_seconds().to_string());
+                    row.push(billed.non_billable().num_seconds().to_string());
+                    row
+                })
+                .collect();
+            output::print_table(format, &header, &rows);
+        }
+    }
+    Ok(())
 }
 
-fn query_all_frames(conn: &Connection) -> Result<Vec<Frame>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, project, start_time, end_time, tags
-         FROM frames",
-    )?;
+/// Sort totals with the most time first, ties by name.
+fn by_duration<K: Ord>(totals: BTreeMap<K, Duration>) -> Vec<(K, Duration)> {
+    let mut sorted: Vec<_> = totals.into_iter().collect();
+    sorted.sort_by_key(|(_, d)| std::cmp::Reverse(*d));
+    sorted
+}
 
-    let frames = stmt
-        .query_map([], |row| row_to_frame(row))?
-        .collect::<Result<Vec<_>, _>>()?;
+/// Print `tree` below the project `path`, describing each node by the
+/// `totals` of the projects it covers.
+fn print_tree(
+    tree: &ProjectTree,
+    totals: &BTreeMap<&str, Billed>,
+    path: &str,
+    depth: usize,
+    describe: &dyn Fn(&Billed) -> String,
+) {
+    for (name, node) in tree.by_total() {
+        let path = if path.is_empty() { name.to_string() } else { format!("{path}{SEPARATOR}{name}") };
+        let mut billed = Billed::default();
+        for (project, b) in totals {
+            if project.strip_prefix(path.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with(SEPARATOR)) {
+                billed += *b;
+            }
+        }
+        println!("{}{} {}", "  ".repeat(depth), name, describe(&billed));
+        print_tree(node, totals, &path, depth + 1, describe);
+    }
+}
+
+/// Usage of each budget that time in the report counts towards, including
+/// budgets on parent projects.
+fn print_budgets(conn: &Connection, frames: &[Frame]) -> Result<()> {
+    let projects: BTreeSet<&str> = frames.iter().map(|f| f.project.as_str()).collect();
+    let mut budgets = BTreeMap::new();
+    for project in projects {
+        for b in budget::covering(conn, project)? {
+            budgets.entry(b.project.clone()).or_insert(b);
+        }
+    }
+
+    let mut lines = Vec::new();
+    for b in budgets.values() {
+        let usage = budget::usage(conn, b)?;
+        lines.push(format!("  {} {}", b.project, usage.summary()));
+    }
 
-    Ok(frames)
+    if !lines.is_empty() {
+        println!("Budgets:");
+        for line in lines {
+            println!("{}", line);
+        }
+    }
+    Ok(())
 }
 
-fn row_to_frame(row: &rusqlite::Row) -> rusqlite::Result<Frame> {
-    let id: i64 = row.get(0)?;
-    let project: String = row.get(1)?;
-    let start_ts: i64 = row.get(2)?;
-    let end_ts: Option<i64> = row.get(3)?;
-    let tags_str: Option<String> = row.get(4)?;
+/// Time on one project at one rate, as it would appear on an invoice.
+/// Non-billable time gets a line of its own without a rate.
+struct EarningsLine<'a> {
+    project: &'a str,
+    billable: bool,
+    rate: Option<&'a Rate>,
+    billed: Billed,
+}
+
+impl EarningsLine<'_> {
+    /// The tag whose rate overrides the project's, if any.
+    fn tag(&self) -> Option<&str> {
+        match self.rate.map(|r| &r.target) {
+            Some(RateTarget::Tag(tag)) => Some(tag),
+            _ => None,
+        }
+    }
 
-    Ok(Frame {
-        id,
-        project,
-        start_time: timestamp_to_local(start_ts),
-        end_time: end_ts.map(timestamp_to_local),
-        tags: tags_str
-            .map(|s| s.split(',').map(String::from).collect())
-            .unwrap_or_default(),
-    })
+    /// Earnings for the rounded time.
+    fn amount(&self) -> Option<i64> {
+        self.rate.map(|r| r.amount(self.billed.rounded.num_seconds()))
+    }
 }
 
-fn print_by_project(frames: &[Frame]) {
-    let mut totals: HashMap<&str, Duration> = HashMap::new();
+/// Earnings per project and rate, grouped by client (the top-level project).
+/// Each line is rounded to the nearest hundredth and subtotals add up the
+/// rounded lines, so they match an invoice built from the lines.
+fn report_earnings(
+    conn: &Connection,
+    frames: &[Frame],
+    range: Option<DateRange>,
+    rounding: Option<&Rounding>,
+    format: Format,
+) -> Result<()> {
+    let rates = Rates::load(conn)?;
+    let lines = rounding::billed(frames, range, rounding, |f| {
+        let rate = rates.for_frame(f).filter(|_| f.billable);
+        vec![(f.project.as_str(), !f.billable, rate.map(|r| &r.target))]
+    });
 
-    for frame in frames {
-        *totals.entry(&frame.project).or_insert(Duration::zero()) += frame.duration();
+    let mut clients: BTreeMap<&str, Vec<EarningsLine>> = BTreeMap::new();
+    for ((project, non_billable, target), billed) in lines {
+        let rate = target.and_then(|t| rates.get(t));
+        let line = EarningsLine { project, billable: !non_billable, rate, billed };
+        let client = line.project.split(SEPARATOR).next().unwrap_or(line.project);
+        clients.entry(client).or_default().push(line);
     }
+    let (total, total_amounts) = sum_lines(clients.values().flatten());
 
-    let mut sorted: Vec<_> = totals.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+    match format {
+        Format::Text => {
+            let amounts = |amounts: &BTreeMap<&str, i64>| {
+                if amounts.is_empty() {
+                    return "-".to_string();
+                }
+                let parts: Vec<String> = amounts
+                    .iter()
+                    .map(|(currency, cents)| format!("{} {}", rate::format_amount(*cents), currency))
+                    .collect();
+                parts.join(", ")
+            };
 
-    let grand_total: Duration = sorted.iter().map(|(_, d)| *d).sum();
+            let mut rows: Vec<[String; 4]> = Vec::new();
+            for (client, lines) in &clients {
+                let (billed, subtotal) = sum_lines(lines);
+                rows.push([client.to_string(), with_details(&billed, rounding, false), String::new(), amounts(&subtotal)]);
+                for line in lines {
+                    let tag = line.tag().map(|t| format!(" +{t}")).unwrap_or_default();
+                    let (rate, amount) = match line.rate {
+                        Some(r) => (
+                            format!("{} {}/h", rate::format_amount(r.cents), r.currency),
+                            format!("{} {}", rate::format_amount(line.amount().unwrap_or(0)), r.currency),
+                        ),
+                        None if !line.billable => ("non-billable".to_string(), "-".to_string()),
+                        None => ("no rate".to_string(), "-".to_string()),
+                    };
+                    rows.push([
+                        format!("  {}{}", line.project, tag),
+                        with_details(&line.billed, rounding, false),
+                        rate,
+                        amount,
+                    ]);
+                }
+            }
+            rows.push(["Total".to_string(), with_details(&total, rounding, false), String::new(), amounts(&total_amounts)]);
 
-    println!("By project:");
-    for (project, duration) in &sorted {
-        println!("  {} {}", project, Frame::format_duration(*duration));
+            if let Some(r) = rounding {
+                println!("Rounded {}", r.describe());
+            }
+            let widths: Vec<usize> = (0..4).map(|i| rows.iter().map(|r| r[i].len()).max().unwrap_or(0)).collect();
+            for row in &rows {
+                let line = format!(
+                    "{:<w0$}  {:>w1$}  {:>w2$}  {:>w3$}",
+                    row[0],
+                    row[1],
+                    row[2],
+                    row[3],
+                    w0 = widths[0],
+                    w1 = widths[1],
+                    w2 = widths[2],
+                    w3 = widths[3]
+                );
+                println!("{}", line.trim_end());
+            }
+        }
+        Format::Json => {
+            let amounts = |amounts: &BTreeMap<&str, i64>| -> Vec<_> {
+                amounts
+                    .iter()
+                    .map(|(currency, cents)| json!({ "currency": currency, "amount": rate::format_amount(*cents) }))
+                    .collect()
+            };
+            let clients: Vec<_> = clients
+                .iter()
+                .map(|(client, lines)| {
+                    let (billed, subtotal) = sum_lines(lines);
+                    let lines: Vec<_> = lines
+                        .iter()
+                        .map(|line| {
+                            let mut entry = json!({
+                                "project": line.project,
+                                "tag": line.tag(),
+                                "seconds": line.billed.raw.num_seconds(),
+                                "rate": line.rate.map(|r| rate::format_amount(r.cents)),
+                                "currency": line.rate.map(|r| &r.currency),
+                                "amount": line.amount().map(rate::format_amount),
+                                "billable": line.billable,
+                            });
+                            if rounding.is_some() {
+                                entry["rounded_seconds"] = line.billed.rounded.num_seconds().into();
+                            }
+                            entry
+                        })
+                        .collect();
+                    let mut entry = json!({
+                        "client": client,
+                        "seconds": billed.raw.num_seconds(),
+                        "amounts": amounts(&subtotal),
+                        "lines": lines,
+                    });
+                    if rounding.is_some() {
+                        entry["rounded_seconds"] = billed.rounded.num_seconds().into();
+                    }
+                    entry
+                })
+                .collect();
+            let mut report = json!({
+                "clients": clients,
+                "total_seconds": total.raw.num_seconds(),
+                "amounts": amounts(&total_amounts),
+            });
+            if rounding.is_some() {
+                report["total_rounded_seconds"] = total.rounded.num_seconds().into();
+            }
+            output::print_json(&report)?;
+        }
+        _ => {
+            let rows: Vec<_> = clients
+                .iter()
+                .flat_map(|(client, lines)| {
+                    lines.iter().map(move |line| {
+                        let mut row = vec![
+                            client.to_string(),
+                            line.project.to_string(),
+                            line.tag().unwrap_or_default().to_string(),
+                            line.billed.raw.num_seconds().to_string(),
+                        ];
+                        if rounding.is_some() {
+                            row.push(line.billed.rounded.num_seconds().to_string());
+                        }
+                        row.extend([
+                            line.rate.map(|r| rate::format_amount(r.cents)).unwrap_or_default(),
+                            line.rate.map(|r| r.currency.clone()).unwrap_or_default(),
+                            line.amount().map(rate::format_amount).unwrap_or_default(),
+                            line.billable.to_string(),
+                        ]);
+                        row
+                    })
+                })
+                .collect();
+            let mut header = vec!["client", "project", "tag", "seconds"];
+            if rounding.is_some() {
+                header.push("rounded_seconds");
+            }
+            header.extend(["rate", "currency", "amount", "billable"]);
+            output::print_table(format, &header, &rows);
+        }
     }
-    println!("Total: {}", Frame::format_duration(grand_total));
+    Ok(())
 }
 
-fn print_by_tag(frames: &[Frame]) {
-    let mut totals: HashMap<&str, Duration> = HashMap::new();
+/// Total time and amount per currency of `lines`.
+fn sum_lines<'a>(
+    lines: impl IntoIterator<Item = &'a EarningsLine<'a>>,
+) -> (Billed, BTreeMap<&'a str, i64>) {
+    let mut amounts: BTreeMap<&str, i64> = BTreeMap::new();
+    let mut billed = Billed::default();
+    for line in lines {
+        billed += line.billed;
+        if let (Some(rate), Some(amount)) = (line.rate, line.amount()) {
+            *amounts.entry(&rate.currency).or_insert(0) += amount;
+        }
+    }
+    (billed, amounts)
+}
 
-    for frame in frames {
-        if frame.tags.is_empty() {
-            *totals.entry("(untagged)").or_insert(Duration::zero()) += frame.duration();
+/// Totals per tag. Frames count towards each of their tags; untagged
+/// frames are grouped together (a `null` tag in JSON, empty in tables).
+fn report_by_tag(
+    frames: &[Frame],
+    range: Option<DateRange>,
+    rounding: Option<&Rounding>,
+    format: Format,
+) -> Result<()> {
+    let totals = rounding::billed(frames, range, rounding, |f| {
+        if f.tags.is_empty() {
+            vec![None]
         } else {
-            for tag in &frame.tags {
-                *totals.entry(tag).or_insert(Duration::zero()) += frame.duration();
+            f.tags.iter().map(|t| Some(t.as_str())).collect()
+        }
+    });
+    let sorted = by_duration(totals.iter().map(|(t, b)| (*t, b.raw)).collect());
+
+    match format {
+        Format::Text => {
+            let billing = frames.iter().any(|f| !f.billable);
+            print_heading("By tag", rounding);
+            for (tag, _) in &sorted {
+                let name = tag.map_or("(untagged)".to_string(), |t| format!("+{t}"));
+                println!("  {} {}", name, with_details(&totals[tag], rounding, billing));
+            }
+        }
+        Format::Json => {
+            let tags: Vec<_> = sorted
+                .iter()
+                .map(|(tag, d)| {
+                    let mut entry = json!({ "tag": tag, "seconds": d.num_seconds() });
+                    if rounding.is_some() {
+                        entry["rounded_seconds"] = totals[tag].rounded.num_seconds().into();
+                    }
+                    add_billable_json(&mut entry, &totals[tag], "");
+                    entry
+                })
+                .collect();
+            output::print_json(&json!({ "tags": tags }))?;
+        }
+        _ => {
+            let mut header = vec!["tag", "seconds"];
+            if rounding.is_some() {
+                header.push("rounded_seconds");
             }
+            header.extend(["billable_seconds", "non_billable_seconds"]);
+            let rows: Vec<_> = sorted
+                .iter()
+                .map(|(tag, d)| {
+                    let billed = &totals[tag];
+                    let mut row = vec![tag.unwrap_or_default().to_string(), d.num_seconds().to_string()];
+                    if rounding.is_some() {
+                        row.push(billed.rounded.num_seconds().to_string());
+                    }
+                    row.push(billed.billable.num_seconds().to_string());
+                    row.push(billed.non_billable().num_seconds().to_string());
+                    row
+                })
+                .collect();
+            output::print_table(format, &header, &rows);
         }
     }
+    Ok(())
+}
 
-    let mut sorted: Vec<_> = totals.into_iter().collect();
-    sorted.sort_by(|a, b| b.1.cmp(&a.1));
+/// Totals for each day, with a line per project. Frames spanning midnight
+/// count towards both days.
+fn report_by_day(frames: &[Frame], range: Option<DateRange>, format: Format) -> Result<()> {
+    let mut days: BTreeMap<NaiveDate, BTreeMap<&str, Duration>> = BTreeMap::new();
+
+    for frame in frames {
+        for (date, duration) in frame.daily_durations() {
+            let in_range = range.is_none_or(|r| r.start <= date && date <= r.end);
+            if in_range && !duration.is_zero() {
+                *days
+                    .entry(date)
+                    .or_default()
+                    .entry(&frame.project)
+                    .or_insert(Duration::zero()) += duration;
+            }
+        }
+    }
+    let days: Vec<_> = days
+        .into_iter()
+        .map(|(date, projects)| {
+            let total = projects.values().copied().sum();
+            (date, total, by_duration(projects))
+        })
+        .collect();
+    let grand_total: Duration = days.iter().map(|(_, total, _)| *total).sum();
+
+    match format {
+        Format::Text => {
+            println!("By day:");
+            for (date, total, projects) in &days {
+                println!("  {} {}", date.format("%Y-%m-%d %a"), Frame::format_duration(*total));
+                for (project, duration) in projects {
+                    println!("    {} {}", project, Frame::format_duration(*duration));
+                }
+            }
+            println!("Total: {}", Frame::format_duration(grand_total));
+        }
+        Format::Json => {
+            let days: Vec<_> = days
+                .iter()
+                .map(|(date, total, projects)| {
+                    let projects: Vec<_> = projects
+                        .iter()
+                        .map(|(p, d)| json!({ "project": p, "seconds": d.num_seconds() }))
+                        .collect();
+                    json!({
+                        "date": date.format("%Y-%m-%d").to_string(),
+                        "seconds": total.num_seconds(),
+                        "projects": projects,
+                    })
+                })
+                .collect();
+            output::print_json(&json!({
+                "days": days,
+                "total_seconds": grand_total.num_seconds(),
+            }))?;
+        }
+        _ => {
+            let rows: Vec<_> = days
+                .iter()
+                .flat_map(|(date, _, projects)| {
+                    projects.iter().map(move |(project, d)| {
+                        vec![
+                            date.format("%Y-%m-%d").to_string(),
+                            project.to_string(),
+                            d.num_seconds().to_string(),
+                        ]
+                    })
+                })
+                .collect();
+            output::print_table(format, &["date", "project", "seconds"], &rows);
+        }
+    }
+    Ok(())
+}
+
+/// Print the grid. JSON and tables give durations in seconds, keyed by
+/// `Grid::key`, with a final `Total` row in tables.
+fn report_grid(grid: &Grid, format: Format) -> Result<()> {
+    let seconds = |cells: &[Duration]| -> Vec<i64> { cells.iter().map(|d| d.num_seconds()).collect() };
+    let keys: Vec<String> = grid.columns.iter().map(|c| grid.key(c)).collect();
+
+    match format {
+        Format::Text if grid.rows.is_empty() => println!("No frames found"),
+        Format::Text => print_grid(grid),
+        Format::Json => {
+            let rows: Vec<_> = grid
+                .rows
+                .iter()
+                .map(|(project, cells)| {
+                    json!({
+                        "project": project,
+                        "seconds": seconds(cells),
+                        "total_seconds": cells.iter().copied().sum::<Duration>().num_seconds(),
+                    })
+                })
+                .collect();
+            output::print_json(&json!({
+                "columns": keys,
+                "rows": rows,
+                "column_seconds": seconds(&grid.column_totals()),
+                "total_seconds": grid.total().num_seconds(),
+            }))?;
+        }
+        _ => {
+            let header: Vec<&str> = std::iter::once("project")
+                .chain(keys.iter().map(String::as_str))
+                .chain(std::iter::once("total"))
+                .collect();
+            let row = |name: &str, cells: &[Duration]| -> Vec<String> {
+                let total: Duration = cells.iter().copied().sum();
+                std::iter::once(name.to_string())
+                    .chain(cells.iter().map(|d| d.num_seconds().to_string()))
+                    .chain(std::iter::once(total.num_seconds().to_string()))
+                    .collect()
+            };
+            let mut rows: Vec<_> = grid.rows.iter().map(|(p, cells)| row(p, cells)).collect();
+            rows.push(row("Total", &grid.column_totals()));
+            output::print_table(format, &header, &rows);
+        }
+    }
+    Ok(())
+}
+
+fn print_grid(grid: &Grid) {
+    let labels: Vec<String> = grid
+        .columns
+        .iter()
+        .map(|c| match grid.period {
+            Period::Day => c.start.format("%a %m-%d").to_string(),
+            _ => grid.key(c),
+        })
+        .collect();
+    let cell = |d: Duration| {
+        if d.is_zero() {
+            "-".to_string()
+        } else {
+            Frame::format_duration(d)
+        }
+    };
+
+    let mut lines: Vec<Vec<String>> = Vec::new();
+    lines.push(
+        std::iter::once("Project".to_string())
+            .chain(labels)
+            .chain(std::iter::once("Total".to_string()))
+            .collect(),
+    );
+    for (project, cells) in &grid.rows {
+        let total: Duration = cells.iter().copied().sum();
+        lines.push(
+            std::iter::once(project.clone())
+                .chain(cells.iter().map(|d| cell(*d)))
+                .chain(std::iter::once(cell(total)))
+                .collect(),
+        );
+    }
+    lines.push(
+        std::iter::once("Total".to_string())
+            .chain(grid.column_totals().into_iter().map(cell))
+            .chain(std::iter::once(cell(grid.total())))
+            .collect(),
+    );
 
-    println!("By tag:");
-    for (tag, duration) in &sorted {
-        let prefix = if *tag == "(untagged)" { "" } else { "+" };
-        println!("  {}{} {}", prefix, tag, Frame::format_duration(*duration));
+    let widths: Vec<usize> = (0..lines[0].len())
+        .map(|i| lines.iter().map(|l| l[i].len()).max().unwrap_or(0))
+        .collect();
+    for line in &lines {
+        let cols: Vec<String> = line
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (text, w))| {
+                if i == 0 {
+                    format!("{text:<w$}")
+                } else {
+                    format!("{text:>w$}")
+                }
+            })
+            .collect();
+        println!("{}", cols.join("  "));
     }
 }
@@ -1,56 +1,25 @@
 use anyhow::Result;
-use rusqlite::{Connection, OptionalExtension};
+use chrono::Local;
+use rusqlite::{Connection, TransactionBehavior};
 
-use crate::frame::{self, timestamp_to_local, Frame};
+use crate::frame::{self, FrameQuery, Order};
+use crate::output;
 
-pub fn run(conn: &Connection) -> Result<()> {
-    if frame::get_current(conn)?.is_some() {
+pub fn run(conn: &mut Connection) -> Result<()> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    if frame::get_current(&tx)?.is_some() {
         anyhow::bail!("already tracking - stop first");
     }
 
-    let last = get_last_frame(conn)?
+    let last = FrameQuery::new()
+        .open(false)
+        .order(Order::EndDesc)
+        .fetch_one(&tx)?
         .ok_or_else(|| anyhow::anyhow!("no previous frame to restart"))?;
 
-    let new_frame = frame::start(conn, &last.project, &last.tags)?;
-    print_started(&new_frame);
+    let new_frame = frame::start(&tx, &last.project, &last.tags, None, Some(last.billable), Local::now())?;
+    tx.commit()?;
+    output::print_started(&new_frame);
     Ok(())
 }
 
-fn get_last_frame(conn: &Connection) -> Result<Option<Frame>> {
-    conn.query_row(
-        "SELECT id, project, start_time, end_time, tags
-         FROM frames
-         WHERE end_time IS NOT NULL
-         ORDER BY end_time DESC
-         LIMIT 1",
-        [],
-        |row| {
-            let id: i64 = row.get(0)?;
-            let project: String = row.get(1)?;
-            let start_ts: i64 = row.get(2)?;
-            let end_ts: Option<i64> = row.get(3)?;
-            let tags_str: Option<String> = row.get(4)?;
-
-            Ok(Frame {
-                id,
-                project,
-                start_time: timestamp_to_local(start_ts),
-                end_time: end_ts.map(timestamp_to_local),
-                tags: tags_str
-                    .map(|s| s.split(',').map(String::from).collect())
-                    .unwrap_or_default(),
-            })
-        },
-    )
-    .optional()
-    .map_err(Into::into)
-}
-
-fn print_started(frame: &Frame) {
-    let tags_str = if frame.tags.is_empty() {
-        String::new()
-    } else {
-        format!(" +{}", frame.tags.join(" +"))
-    };
-    println!("Started {}{}", frame.project, tags_str);
-}
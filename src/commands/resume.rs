@@ -0,0 +1,24 @@
+use anyhow::Result;
+use chrono::Local;
+use rusqlite::Connection;
+
+use crate::frame::{self, Frame};
+
+pub fn run(conn: &Connection) -> Result<()> {
+    let frame = frame::get_current(conn)?
+        .ok_or_else(|| anyhow::anyhow!("not tracking"))?;
+
+    let since = frame
+        .paused_since()
+        .ok_or_else(|| anyhow::anyhow!("not paused"))?;
+
+    let now = Local::now();
+    frame::resume(conn, frame.id, now)?;
+
+    println!(
+        "Resumed {} (paused {})",
+        frame.label(),
+        Frame::format_duration(now.signed_duration_since(since))
+    );
+    Ok(())
+}
@@ -1,23 +1,57 @@
 use anyhow::{Result, bail};
-use rusqlite::Connection;
+use chrono::{DateTime, Local};
+use rusqlite::{Connection, TransactionBehavior};
 
-use crate::frame::{self, Frame};
+use crate::frame;
+use crate::output;
 
-pub fn run(conn: &Connection, project: &str, tags: &[String]) -> Result<()> {
-    if frame::get_current(conn)?.is_some() {
-        bail!("already tracking - stop first");
+pub fn run(
+    conn: &mut Connection,
+    project: &str,
+    tags: &[String],
+    notes: Option<&str>,
+    billable: Option<bool>,
+    at: Option<DateTime<Local>>,
+    switch: bool,
+) -> Result<()> {
+    let at = at.unwrap_or_else(Local::now);
+    if at > Local::now() {
+        bail!("start time is in the future");
     }
 
-    let frame = frame::start(conn, project, tags)?;
-    print_started(&frame);
+    // IMMEDIATE takes the write lock before the check, so a concurrent
+    // start can't slip in between the check and the insert
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let mut stopped = None;
+    if let Some(mut current) = frame::get_current(&tx)? {
+        if !switch {
+            bail!("already tracking - stop first (or use --switch)");
+        }
+        if at < current.start_time {
+            bail!(
+                "start time is before the current frame started ({})",
+                current.start_time.format("%Y-%m-%d %H:%M")
+            );
+        }
+        frame::stop(&tx, current.id, None, at)?;
+        current.end_time = Some(at);
+        stopped = Some(current);
+    }
+    if let Some((id, end)) = frame::ending_after(&tx, at)? {
+        bail!(
+            "start time overlaps frame {} (ended {})",
+            id,
+            end.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    let frame = frame::start(&tx, project, tags, notes, billable, at)?;
+    tx.commit()?;
+
+    if let Some(ref current) = stopped {
+        output::print_stopped(current);
+    }
+    output::print_started(&frame);
     Ok(())
 }
 
-fn print_started(frame: &Frame) {
-    let tags_str = if frame.tags.is_empty() {
-        String::new()
-    } else {
-        format!(" +{}", frame.tags.join(" +"))
-    };
-    println!("Started {}{}", frame.project, tags_str);
-}
@@ -1,22 +1,140 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::Local;
 use rusqlite::Connection;
+use serde_json::json;
 
-use crate::frame::{self, Frame};
+use crate::budget;
+use crate::frame::{self, Frame, FrameQuery};
+use crate::output::{self, Format, FrameRecord};
+use crate::template;
+use crate::time::DateRange;
 
-pub fn run(conn: &Connection) -> Result<()> {
-    match frame::get_current(conn)? {
-        Some(frame) => print_status(&frame),
-        None => println!("Not tracking"),
+/// Print the running frame. `template` replaces the default line (and the
+/// budget lines); `not_tracking` is printed when nothing is running.
+pub fn run(
+    conn: &Connection,
+    format: Format,
+    template: Option<&str>,
+    not_tracking: &str,
+) -> Result<()> {
+    let current = frame::get_current(conn)?;
+    match format {
+        Format::Text => {}
+        Format::Json => return print_json(conn, current.as_ref()),
+        _ => {
+            let rows: Vec<_> = current.iter().map(|f| FrameRecord::from(f).to_row()).collect();
+            output::print_table(format, FrameRecord::HEADER, &rows);
+            return Ok(());
+        }
+    }
+
+    let Some(frame) = current else {
+        println!("{}", not_tracking);
+        return Ok(());
+    };
+
+    if let Some(template) = template {
+        println!("{}", render(conn, &frame, template)?);
+        return Ok(());
+    }
+
+    print_status(&frame);
+    for b in budget::covering(conn, &frame.project)? {
+        let usage = budget::usage(conn, &b)?;
+        if b.project == frame.project {
+            println!("Budget: {}", usage.summary());
+        } else {
+            println!("Budget for {}: {}", b.project, usage.summary());
+        }
+        if usage.is_over() {
+            eprintln!("Warning: {} is over budget", b.project);
+        }
     }
     Ok(())
 }
 
 fn print_status(frame: &Frame) {
     let duration = Frame::format_duration(frame.duration());
-    let tags_str = if frame.tags.is_empty() {
-        String::new()
-    } else {
-        format!(" +{}", frame.tags.join(" +"))
+    match frame.paused_since() {
+        Some(since) => {
+            let paused = Frame::format_duration(Local::now().signed_duration_since(since));
+            println!("{} ({}) paused for {}", frame.label(), duration, paused);
+        }
+        None => println!("{} ({})", frame.label(), duration),
+    }
+}
+
+/// `{"tracking": false}`, or the running frame's fields plus `tracking`,
+/// `paused` and `budget`: the nearest budget covering the project (its own or
+/// a parent's), or null.
+fn print_json(conn: &Connection, frame: Option<&Frame>) -> Result<()> {
+    let Some(frame) = frame else {
+        return output::print_json(&json!({ "tracking": false }));
     };
-    println!("{}{} ({})", frame.project, tags_str, duration);
+
+    let nearest = budget::covering(conn, &frame.project)?.into_iter().next();
+    let budget = nearest.map(|b| -> Result<_> {
+        let usage = budget::usage(conn, &b)?;
+        Ok(json!({
+            "project": b.project,
+            "consumed_seconds": usage.consumed.num_seconds(),
+            "limit_seconds": usage.limit.num_seconds(),
+            "period": usage.period.as_str(),
+            "over": usage.is_over(),
+        }))
+    });
+    let budget = budget.transpose()?;
+    let mut value = serde_json::to_value(FrameRecord::from(frame))?;
+    value["tracking"] = true.into();
+    value["paused"] = frame.paused_since().is_some().into();
+    value["budget"] = budget.into();
+    output::print_json(&value)
+}
+
+/// Fill in a `--template` for the running frame.
+fn render(conn: &Connection, frame: &Frame, template: &str) -> Result<String> {
+    template::render(template, |name, spec| {
+        Ok(match name {
+            "project" => frame.project.clone(),
+            "tags" => frame
+                .tags
+                .iter()
+                .map(|t| format!("+{t}"))
+                .collect::<Vec<_>>()
+                .join(" "),
+            "id" => frame.id.to_string(),
+            "note" => frame.notes.clone().unwrap_or_default(),
+            "start" => {
+                let spec = spec.unwrap_or("%H:%M");
+                if StrftimeItems::new(spec).any(|item| item == Item::Error) {
+                    bail!("invalid start format '{spec}'");
+                }
+                frame.start_time.format(spec).to_string()
+            }
+            "elapsed" => template::format_duration(frame.duration(), spec)?,
+            "today" => template::format_duration(today_total(conn, &frame.project)?, spec)?,
+            "paused" => {
+                let paused = frame.paused_since().is_some();
+                if paused { "paused" } else { "" }.to_string()
+            }
+            _ => bail!(
+                "unknown template field '{name}' \
+                 (expected project, tags, id, note, start, elapsed, today or paused)"
+            ),
+        })
+    })
+}
+
+/// Time tracked on `project` today, including the running frame.
+fn today_total(conn: &Connection, project: &str) -> Result<chrono::Duration> {
+    let today = DateRange::day(Local::now().date_naive());
+    let (from, to) = today.bounds();
+    Ok(FrameQuery::new()
+        .project(project)
+        .range(Some(today))
+        .fetch(conn)?
+        .iter()
+        .map(|f| f.duration_between(from, to))
+        .sum())
 }
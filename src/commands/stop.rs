@@ -1,23 +1,37 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Local};
 use rusqlite::Connection;
 
-use crate::frame::{self, Frame};
+use crate::budget;
+use crate::frame;
+use crate::output;
 
-pub fn run(conn: &Connection) -> Result<()> {
-    let frame = frame::get_current(conn)?
+pub fn run(conn: &Connection, notes: Option<&str>, at: Option<DateTime<Local>>) -> Result<()> {
+    let mut frame = frame::get_current(conn)?
         .ok_or_else(|| anyhow::anyhow!("not tracking"))?;
 
-    frame::stop(conn, frame.id)?;
-    print_stopped(&frame);
+    let at = at.unwrap_or_else(Local::now);
+    if at > Local::now() {
+        bail!("stop time is in the future");
+    }
+    if at < frame.start_time {
+        bail!(
+            "stop time is before the frame started ({})",
+            frame.start_time.format("%Y-%m-%d %H:%M")
+        );
+    }
+
+    frame::stop(conn, frame.id, notes, at)?;
+    frame.end_time = Some(at);
+    output::print_stopped(&frame);
+
+    // Warn only if this frame is what pushed a budget over
+    for b in budget::covering(conn, &frame.project)? {
+        let usage = budget::usage(conn, &b)?;
+        if usage.is_over() && usage.consumed - frame.duration() <= usage.limit {
+            eprintln!("Warning: {} is now over budget ({})", b.project, usage.summary());
+        }
+    }
     Ok(())
 }
 
-fn print_stopped(frame: &Frame) {
-    let duration = Frame::format_duration(frame.duration());
-    let tags_str = if frame.tags.is_empty() {
-        String::new()
-    } else {
-        format!(" +{}", frame.tags.join(" +"))
-    };
-    println!("Stopped {}{} ({})", frame.project, tags_str, duration);
-}
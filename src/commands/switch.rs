@@ -1,8 +1,10 @@
 use anyhow::Result;
-use rusqlite::Connection;
+use chrono::Local;
+use rusqlite::{Connection, TransactionBehavior};
 
-use crate::frame::{self, Frame};
+use crate::frame;
 use crate::git;
+use crate::output;
 
 pub fn run(conn: &mut Connection, quiet: bool) -> Result<()> {
     let Some((repo_name, branch)) = git::get_context() else {
@@ -10,7 +12,9 @@ pub fn run(conn: &mut Connection, quiet: bool) -> Result<()> {
         return Ok(());
     };
 
-    let current = frame::get_current(conn)?;
+    // Use an IMMEDIATE transaction so the check and stop+start are atomic
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let current = frame::get_current(&tx)?;
 
     // Check if already tracking the same project+tag
     if let Some(ref frame) = current {
@@ -20,42 +24,23 @@ pub fn run(conn: &mut Connection, quiet: bool) -> Result<()> {
         }
     }
 
-    // Use transaction to make stop+start atomic
-    let tx = conn.transaction()?;
+    let now = Local::now();
 
     // Stop current frame if exists
     if let Some(ref frame) = current {
-        frame::stop(&tx, frame.id)?;
+        frame::stop(&tx, frame.id, None, now)?;
         if !quiet {
-            print_stopped(frame);
+            output::print_stopped(frame);
         }
     }
 
     // Start new frame with git context
-    let new_frame = frame::start(&tx, &repo_name, &[branch])?;
+    let new_frame = frame::start(&tx, &repo_name, &[branch], None, None, now)?;
     if !quiet {
-        print_started(&new_frame);
+        output::print_started(&new_frame);
     }
 
     tx.commit()?;
     Ok(())
 }
 
-fn print_stopped(frame: &Frame) {
-    let duration = Frame::format_duration(frame.duration());
-    let tags_str = if frame.tags.is_empty() {
-        String::new()
-    } else {
-        format!(" +{}", frame.tags.join(" +"))
-    };
-    println!("Stopped {}{} ({})", frame.project, tags_str, duration);
-}
-
-fn print_started(frame: &Frame) {
-    let tags_str = if frame.tags.is_empty() {
-        String::new()
-    } else {
-        format!(" +{}", frame.tags.join(" +"))
-    };
-    println!("Started {}{}", frame.project, tags_str);
-}
@@ -1,22 +1,21 @@
 use anyhow::Result;
 use rusqlite::Connection;
-use std::collections::BTreeSet;
 
-pub fn run(conn: &Connection) -> Result<()> {
-    let mut stmt = conn.prepare("SELECT DISTINCT tags FROM frames WHERE tags IS NOT NULL")?;
+use crate::output::{self, Format};
 
-    let all_tags: BTreeSet<String> = stmt
-        .query_map([], |row| row.get::<_, String>(0))?
-        .filter_map(|r| r.ok())
-        .flat_map(|tags_str| {
-            tags_str
-                .split(',')
-                .map(String::from)
-                .collect::<Vec<_>>()
-        })
-        .collect();
+pub fn run(conn: &Connection, format: Format) -> Result<()> {
+    let mut stmt = conn.prepare("SELECT DISTINCT tag FROM frame_tags ORDER BY tag")?;
 
-    if all_tags.is_empty() {
+    let all_tags: Vec<String> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if format == Format::Json {
+        output::print_json(&all_tags)?;
+    } else if format != Format::Text {
+        let rows: Vec<_> = all_tags.into_iter().map(|t| vec![t]).collect();
+        output::print_table(format, &["tag"], &rows);
+    } else if all_tags.is_empty() {
         println!("No tags found");
     } else {
         for tag in all_tags {
@@ -0,0 +1,49 @@
+use anyhow::{bail, Result};
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::journal;
+
+/// Reverse the last `count` journaled operations, newest first, in one
+/// transaction.
+pub fn run(conn: &mut Connection, count: usize) -> Result<()> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let operations = journal::recent(&tx, count)?;
+    if operations.is_empty() {
+        bail!("nothing to undo");
+    }
+    if operations.len() < count {
+        bail!("only {} operation(s) to undo", operations.len());
+    }
+
+    let mut notes = Vec::new();
+    for op in &operations {
+        notes.extend(journal::undo(&tx, op)?);
+    }
+    tx.commit()?;
+
+    for op in &operations {
+        println!("Undid {}", op.description);
+    }
+    for note in notes {
+        println!("Note: {}", note);
+    }
+    Ok(())
+}
+
+pub fn list(conn: &Connection) -> Result<()> {
+    let operations = journal::recent(conn, usize::MAX)?;
+    if operations.is_empty() {
+        println!("Nothing to undo");
+        return Ok(());
+    }
+
+    for (i, op) in operations.iter().enumerate() {
+        println!(
+            "{:>3}  {}  {}",
+            i + 1,
+            op.created_at.format("%Y-%m-%d %H:%M"),
+            op.description
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Weekday};
+use directories::{BaseDirs, ProjectDirs};
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::frame::DurationFormat;
+use crate::rounding::{RoundMode, RoundPer};
+use crate::time;
+
+/// Settings from `config.toml`. Command-line flags and environment
+/// variables take precedence over these.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Database location (`TIMER_CLI_DB` wins)
+    pub db_path: Option<PathBuf>,
+    /// Range for `log` and `report` when none is given
+    pub default_range: DefaultRange,
+    pub duration_format: DurationFormat,
+    /// First day of the week for `--week`, `this week` and budgets
+    pub week_start: Option<Weekday>,
+    /// Make `start` behave as if `--switch` was given
+    pub start_switch: bool,
+    /// Billing increment for `report` and `export`
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub round_to: Option<Duration>,
+    pub round_mode: RoundMode,
+    pub round_per: RoundPer,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultRange {
+    #[default]
+    Day,
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+/// Known keys with a description of their values, for `config` errors and
+/// help.
+pub const KEYS: &[(&str, &str)] = &[
+    ("db_path", "path to the database file"),
+    ("default_range", "day, week, month, year or all"),
+    ("duration_format", "default (1h 5m), hm (1:05) or decimal (1.08h)"),
+    ("week_start", "a weekday, e.g. monday or sunday"),
+    ("start_switch", "true or false"),
+    ("round_to", "a billing increment, e.g. 6m or 15m"),
+    ("round_mode", "up, nearest or down"),
+    ("round_per", "frame or day"),
+];
+
+/// `TIMER_CLI_CONFIG`, or `config.toml` in the platform config directory.
+pub fn path() -> Result<PathBuf> {
+    if let Ok(path) = std::env::var("TIMER_CLI_CONFIG") {
+        return Ok(PathBuf::from(path));
+    }
+
+    let proj = ProjectDirs::from("", "", "timer-cli")
+        .context("could not determine home directory")?;
+    Ok(proj.config_dir().join("config.toml"))
+}
+
+/// The raw settings table, empty if there is no config file.
+pub fn read_table() -> Result<toml::Table> {
+    let path = path()?;
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read {}", path.display()))?;
+    text.parse()
+        .with_context(|| format!("invalid config file {}", path.display()))
+}
+
+/// Check that `table` holds only known settings with valid values.
+pub fn validate(table: &toml::Table) -> Result<()> {
+    parse(table.clone()).map(|_| ())
+}
+
+/// Save `table` as the config file.
+pub fn write_table(table: &toml::Table) -> Result<()> {
+    let path = path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).context("failed to create config directory")?;
+    }
+    fs::write(&path, toml::to_string(table)?)
+        .with_context(|| format!("failed to write {}", path.display()))
+}
+
+pub fn load() -> Result<Config> {
+    let path = path()?;
+    parse(read_table()?).with_context(|| format!("invalid config file {}", path.display()))
+}
+
+fn parse(table: toml::Table) -> Result<Config> {
+    if let Some(key) = table.keys().find(|k| !KEYS.iter().any(|(name, _)| name == k)) {
+        bail!(
+            "unknown setting '{key}' (expected one of: {}; remove it with `timer config unset {key}`)",
+            key_names()
+        );
+    }
+    let mut config: Config = toml::Value::Table(table).try_into()?;
+
+    // Allow ~/ in paths
+    if let Some(rest) = config.db_path.as_ref().and_then(|p| p.strip_prefix("~").ok()) {
+        let home = BaseDirs::new().context("could not determine home directory")?;
+        config.db_path = Some(home.home_dir().join(rest));
+    }
+    Ok(config)
+}
+
+fn deserialize_duration<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+    let s = String::deserialize(d)?;
+    time::parse_duration(&s).map(Some).map_err(serde::de::Error::custom)
+}
+
+pub fn key_names() -> String {
+    KEYS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+}
@@ -1,14 +1,273 @@
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use rusqlite::Connection;
+use rusqlite::{Connection, TransactionBehavior};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-pub fn get_db_path() -> Result<PathBuf> {
+/// A single schema upgrade step. Steps run in order, each inside its own
+/// transaction that also bumps `PRAGMA user_version` to `version`.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    /// Query run before `sql`, one line of text per row, for changes to data
+    /// the user should hear about.
+    notice: Option<&'static str>,
+    sql: &'static str,
+}
+
+/// Ordered list of schema migrations. Append only - never edit a shipped step.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "create frames table",
+        notice: None,
+        sql: "
+            CREATE TABLE IF NOT EXISTS frames (
+                id INTEGER PRIMARY KEY,
+                project TEXT NOT NULL,
+                start_time INTEGER NOT NULL,
+                end_time INTEGER,
+                tags TEXT
+            );
+            CREATE INDEX IF NOT EXISTS idx_frames_project ON frames(project);
+            CREATE INDEX IF NOT EXISTS idx_frames_start ON frames(start_time);
+            ",
+    },
+    Migration {
+        version: 2,
+        description: "move tags into frame_tags table",
+        notice: None,
+        sql: "
+            CREATE TABLE frame_tags (
+                frame_id INTEGER NOT NULL REFERENCES frames(id) ON DELETE CASCADE,
+                tag TEXT NOT NULL,
+                position INTEGER NOT NULL,
+                PRIMARY KEY (frame_id, tag)
+            );
+            CREATE INDEX idx_frame_tags_tag ON frame_tags(tag);
+
+            -- Split the old comma-joined column, keeping tag order
+            WITH RECURSIVE split(frame_id, tag, rest, position) AS (
+                SELECT id, NULL, tags || ',', -1 FROM frames WHERE tags IS NOT NULL
+                UNION ALL
+                SELECT frame_id,
+                       substr(rest, 1, instr(rest, ',') - 1),
+                       substr(rest, instr(rest, ',') + 1),
+                       position + 1
+                FROM split WHERE rest <> ''
+            )
+            INSERT OR IGNORE INTO frame_tags (frame_id, tag, position)
+            SELECT frame_id, tag, position FROM split WHERE tag <> '';
+
+            ALTER TABLE frames DROP COLUMN tags;
+            ",
+    },
+    Migration {
+        version: 3,
+        description: "add notes to frames",
+        notice: None,
+        sql: "ALTER TABLE frames ADD COLUMN notes TEXT;",
+    },
+    Migration {
+        version: 4,
+        description: "allow only one open frame",
+        notice: Some(
+            "SELECT CASE WHEN EXISTS (SELECT 1 FROM frames f WHERE f.start_time > frames.start_time)
+                         THEN 'Ended' ELSE 'Deleted' END
+                    || ' stray open frame ' || id || ' (' || project || ', started '
+                    || strftime('%Y-%m-%d %H:%M', start_time, 'unixepoch', 'localtime') || ')'
+                    || CASE WHEN EXISTS (SELECT 1 FROM frames f WHERE f.start_time > frames.start_time)
+                            THEN ' where the next frame begins'
+                            ELSE ', as the running frame starts at the same time' END
+             FROM frames
+             WHERE end_time IS NULL
+               AND id <> (SELECT id FROM frames WHERE end_time IS NULL
+                          ORDER BY start_time DESC, id DESC LIMIT 1)
+             ORDER BY id",
+        ),
+        sql: "
+            -- Stray open frames that start with the running frame would end
+            -- up empty, so drop them
+            CREATE TEMP TABLE empty_strays AS
+                SELECT id FROM frames
+                WHERE end_time IS NULL
+                  AND id <> (SELECT id FROM frames WHERE end_time IS NULL
+                             ORDER BY start_time DESC, id DESC LIMIT 1)
+                  AND NOT EXISTS (SELECT 1 FROM frames f WHERE f.start_time > frames.start_time);
+            DELETE FROM frame_tags WHERE frame_id IN (SELECT id FROM empty_strays);
+            DELETE FROM frames WHERE id IN (SELECT id FROM empty_strays);
+            DROP TABLE empty_strays;
+
+            -- End the other stray open frames where the next frame begins
+            UPDATE frames
+            SET end_time = (SELECT MIN(f.start_time) FROM frames f
+                            WHERE f.start_time > frames.start_time)
+            WHERE end_time IS NULL
+              AND id <> (SELECT id FROM frames WHERE end_time IS NULL
+                         ORDER BY start_time DESC, id DESC LIMIT 1);
+
+            CREATE UNIQUE INDEX idx_frames_single_open
+                ON frames((end_time IS NULL)) WHERE end_time IS NULL;
+            ",
+    },
+    Migration {
+        version: 5,
+        description: "add frame_breaks table for pauses",
+        notice: None,
+        sql: "
+            CREATE TABLE frame_breaks (
+                id INTEGER PRIMARY KEY,
+                frame_id INTEGER NOT NULL REFERENCES frames(id) ON DELETE CASCADE,
+                start_time INTEGER NOT NULL,
+                end_time INTEGER
+            );
+            CREATE INDEX idx_frame_breaks_frame ON frame_breaks(frame_id);
+            ",
+    },
+    Migration {
+        version: 6,
+        description: "add budgets table",
+        notice: None,
+        sql: "
+            CREATE TABLE budgets (
+                project TEXT PRIMARY KEY,
+                seconds INTEGER NOT NULL,
+                period TEXT NOT NULL
+            );
+            ",
+    },
+    Migration {
+        version: 7,
+        description: "add uuid, created_at and updated_at to frames",
+        notice: None,
+        sql: "
+            ALTER TABLE frames ADD COLUMN uuid TEXT;
+            ALTER TABLE frames ADD COLUMN created_at INTEGER;
+            ALTER TABLE frames ADD COLUMN updated_at INTEGER;
+
+            -- Random (version 4) UUIDs, generated in SQL so that frames
+            -- inserted by other writers such as TimerBar get one too
+            UPDATE frames SET
+                uuid = lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+                       substr(lower(hex(randomblob(2))), 2) || '-' ||
+                       substr('89ab', 1 + abs(random() % 4), 1) ||
+                       substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6))),
+                created_at = start_time,
+                updated_at = COALESCE(end_time, start_time);
+            CREATE UNIQUE INDEX idx_frames_uuid ON frames(uuid);
+
+            CREATE TRIGGER frames_defaults AFTER INSERT ON frames
+            WHEN NEW.uuid IS NULL OR NEW.created_at IS NULL OR NEW.updated_at IS NULL
+            BEGIN
+                UPDATE frames SET
+                    uuid = COALESCE(uuid,
+                        lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+                        substr(lower(hex(randomblob(2))), 2) || '-' ||
+                        substr('89ab', 1 + abs(random() % 4), 1) ||
+                        substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)))),
+                    created_at = COALESCE(created_at, CAST(strftime('%s', 'now') AS INTEGER)),
+                    updated_at = COALESCE(updated_at, CAST(strftime('%s', 'now') AS INTEGER))
+                WHERE id = NEW.id;
+            END;
+
+            -- Any change to a frame, its tags or its breaks bumps updated_at,
+            -- unless the statement sets updated_at itself (e.g. import)
+            CREATE TRIGGER frames_touch AFTER UPDATE ON frames
+            WHEN NEW.updated_at IS OLD.updated_at
+            BEGIN
+                UPDATE frames SET updated_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = NEW.id;
+            END;
+            CREATE TRIGGER frame_tags_insert_touch AFTER INSERT ON frame_tags
+            BEGIN
+                UPDATE frames SET updated_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = NEW.frame_id;
+            END;
+            CREATE TRIGGER frame_tags_delete_touch AFTER DELETE ON frame_tags
+            BEGIN
+                UPDATE frames SET updated_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = OLD.frame_id;
+            END;
+            CREATE TRIGGER frame_breaks_insert_touch AFTER INSERT ON frame_breaks
+            BEGIN
+                UPDATE frames SET updated_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = NEW.frame_id;
+            END;
+            CREATE TRIGGER frame_breaks_update_touch AFTER UPDATE ON frame_breaks
+            BEGIN
+                UPDATE frames SET updated_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = NEW.frame_id;
+            END;
+            CREATE TRIGGER frame_breaks_delete_touch AFTER DELETE ON frame_breaks
+            BEGIN
+                UPDATE frames SET updated_at = CAST(strftime('%s', 'now') AS INTEGER) WHERE id = OLD.frame_id;
+            END;
+            ",
+    },
+    Migration {
+        version: 8,
+        description: "add operations journal for undo",
+        notice: None,
+        sql: "
+            CREATE TABLE operations (
+                id INTEGER PRIMARY KEY,
+                created_at INTEGER NOT NULL,
+                description TEXT NOT NULL,
+                -- JSON array of the affected frames as they were before
+                frames TEXT NOT NULL
+            );
+            ",
+    },
+    Migration {
+        version: 9,
+        description: "add rates table",
+        notice: None,
+        sql: "
+            CREATE TABLE rates (
+                kind TEXT NOT NULL,          -- 'project' or 'tag'
+                name TEXT NOT NULL,
+                cents INTEGER NOT NULL,      -- per hour, in hundredths of the currency
+                currency TEXT NOT NULL,
+                PRIMARY KEY (kind, name)
+            );
+            ",
+    },
+    Migration {
+        version: 10,
+        description: "add billable flag to frames and per-project defaults",
+        notice: None,
+        sql: "
+            -- NULL on older frames, which count as billable
+            ALTER TABLE frames ADD COLUMN billable INTEGER;
+            CREATE TABLE project_billing (
+                project TEXT PRIMARY KEY,    -- covers its sub-projects too
+                billable INTEGER NOT NULL
+            );
+            -- Frames inserted without a flag get the default of their project
+            -- or nearest parent project, so other writers get it too
+            CREATE TRIGGER frames_billable_default AFTER INSERT ON frames
+            WHEN NEW.billable IS NULL
+            BEGIN
+                UPDATE frames SET billable = COALESCE(
+                    (SELECT billable FROM project_billing
+                     WHERE project = NEW.project
+                        OR substr(NEW.project, 1, length(project) + 1) = project || '/'
+                     ORDER BY length(project) DESC LIMIT 1),
+                    1
+                ) WHERE id = NEW.id;
+            END;
+            ",
+    },
+];
+
+/// Schema version written by this build, stored in `PRAGMA user_version` so
+/// other readers (e.g. TimerBar) can check compatibility.
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+/// `TIMER_CLI_DB`, then the configured path, then the platform data directory.
+pub fn get_db_path(configured: Option<&Path>) -> Result<PathBuf> {
     // Allow override for testing
     if let Ok(path) = std::env::var("TIMER_CLI_DB") {
         return Ok(PathBuf::from(path));
     }
+    if let Some(path) = configured {
+        return Ok(path.to_path_buf());
+    }
 
     let proj = ProjectDirs::from("", "", "timer-cli")
         .context("could not determine home directory")?;
@@ -17,8 +276,16 @@ pub fn get_db_path() -> Result<PathBuf> {
     Ok(data_dir.join("frames.db"))
 }
 
-pub fn open() -> Result<Connection> {
-    let path = get_db_path()?;
+/// Open the database and bring its schema up to date.
+pub fn open(configured: Option<&Path>) -> Result<Connection> {
+    let mut conn = connect(configured)?;
+    migrate(&mut conn)?;
+    Ok(conn)
+}
+
+/// Open the database without running migrations.
+pub fn connect(configured: Option<&Path>) -> Result<Connection> {
+    let path = get_db_path(configured)?;
     let conn = Connection::open(&path)
         .with_context(|| format!("failed to open database at {}", path.display()))?;
 
@@ -26,25 +293,63 @@ pub fn open() -> Result<Connection> {
     conn.pragma_update(None, "journal_mode", "WAL")?;
     // Wait up to 5 seconds if database is locked
     conn.busy_timeout(std::time::Duration::from_secs(5))?;
+    // frame_tags rows are removed with their frame
+    conn.pragma_update(None, "foreign_keys", true)?;
 
-    init_schema(&conn)?;
     Ok(conn)
 }
 
-fn init_schema(conn: &Connection) -> Result<()> {
-    conn.execute_batch(
-        "
-        CREATE TABLE IF NOT EXISTS frames (
-            id INTEGER PRIMARY KEY,
-            project TEXT NOT NULL,
-            start_time INTEGER NOT NULL,
-            end_time INTEGER,
-            tags TEXT
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+        .context("failed to read schema version")
+}
+
+/// Migrations not yet applied to this database, in the order they will run.
+pub fn pending_migrations(conn: &Connection) -> Result<Vec<&'static Migration>> {
+    let version = check_version(conn)?;
+    Ok(MIGRATIONS.iter().filter(|m| m.version > version).collect())
+}
+
+/// Apply all pending migrations and return the ones that ran.
+pub fn migrate(conn: &mut Connection) -> Result<Vec<&'static Migration>> {
+    let mut applied = Vec::new();
+
+    for migration in pending_migrations(conn)? {
+        // IMMEDIATE takes the write lock up front so a concurrent process
+        // can't apply the same step between our version check and the DDL.
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        if check_version(&tx)? >= migration.version {
+            continue;
+        }
+
+        if let Some(notice) = migration.notice {
+            let mut stmt = tx.prepare(notice)?;
+            for line in stmt.query_map([], |row| row.get::<_, String>(0))? {
+                eprintln!("{}", line?);
+            }
+        }
+        tx.execute_batch(migration.sql).with_context(|| {
+            format!(
+                "failed to apply migration {} ({})",
+                migration.version, migration.description
+            )
+        })?;
+        tx.pragma_update(None, "user_version", migration.version)?;
+        tx.commit()?;
+        applied.push(migration);
+    }
+
+    Ok(applied)
+}
+
+fn check_version(conn: &Connection) -> Result<i64> {
+    let version = schema_version(conn)?;
+    if version > SCHEMA_VERSION {
+        anyhow::bail!(
+            "database schema version {} is newer than this timer-cli supports ({}) - please upgrade",
+            version,
+            SCHEMA_VERSION
         );
-        CREATE INDEX IF NOT EXISTS idx_frames_project ON frames(project);
-        CREATE INDEX IF NOT EXISTS idx_frames_start ON frames(start_time);
-        ",
-    )
-    .context("failed to initialize database schema")?;
-    Ok(())
+    }
+    Ok(version)
 }
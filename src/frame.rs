@@ -1,23 +1,113 @@
 use anyhow::{Context, Result};
-use chrono::{DateTime, Duration, Local, LocalResult, TimeZone};
-use rusqlite::{Connection, OptionalExtension, params};
+use chrono::{DateTime, Duration, Local, LocalResult, NaiveDate, TimeZone};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, ErrorCode, OptionalExtension};
+use serde::Deserialize;
+use std::sync::OnceLock;
+
+use crate::time::DateRange;
 
 #[derive(Debug)]
 pub struct Frame {
     pub id: i64,
+    /// Stable identity across databases, used to match frames on import
+    pub uuid: String,
     pub project: String,
     pub start_time: DateTime<Local>,
     pub end_time: Option<DateTime<Local>>,
     pub tags: Vec<String>,
+    pub notes: Option<String>,
+    pub breaks: Vec<Break>,
+    /// Whether the time can be billed to a client
+    pub billable: bool,
+    pub created_at: DateTime<Local>,
+    /// Bumped by the database whenever the frame, its tags or breaks change
+    pub updated_at: DateTime<Local>,
+}
+
+/// A pause within a frame. An open break means the frame is paused.
+#[derive(Debug, Clone)]
+pub struct Break {
+    pub start_time: DateTime<Local>,
+    pub end_time: Option<DateTime<Local>>,
 }
 
 impl Frame {
+    /// Time worked, excluding pauses.
     pub fn duration(&self) -> Duration {
         let end = self.end_time.unwrap_or_else(Local::now);
-        end.signed_duration_since(self.start_time)
+        end.signed_duration_since(self.start_time) - self.paused_duration()
+    }
+
+    /// Total time spent paused, clipped to the frame.
+    pub fn paused_duration(&self) -> Duration {
+        let frame_end = self.end_time.unwrap_or_else(Local::now);
+        self.breaks
+            .iter()
+            .map(|b| {
+                let start = b.start_time.max(self.start_time);
+                let end = b.end_time.unwrap_or(frame_end).min(frame_end);
+                end.signed_duration_since(start).max(Duration::zero())
+            })
+            .sum()
+    }
+
+    /// Time worked between `from` and `to`, excluding pauses.
+    pub fn duration_between(&self, from: DateTime<Local>, to: DateTime<Local>) -> Duration {
+        let frame_end = self.end_time.unwrap_or_else(Local::now);
+        let paused: Duration = self
+            .breaks
+            .iter()
+            .map(|b| {
+                let start = b.start_time.max(self.start_time);
+                let end = b.end_time.unwrap_or(frame_end).min(frame_end);
+                overlap(start, end, from, to)
+            })
+            .sum();
+        overlap(self.start_time, frame_end, from, to) - paused
+    }
+
+    /// Time worked on each local calendar day the frame touches.
+    pub fn daily_durations(&self) -> Vec<(NaiveDate, Duration)> {
+        let end = self.end_time.unwrap_or_else(Local::now);
+        self.start_time
+            .date_naive()
+            .iter_days()
+            .take_while(|date| *date <= end.date_naive())
+            .map(|date| {
+                let (from, to) = DateRange::day(date).bounds();
+                (date, self.duration_between(from, to))
+            })
+            .collect()
+    }
+
+    /// Start of the current pause, if the frame is paused.
+    pub fn paused_since(&self) -> Option<DateTime<Local>> {
+        self.breaks
+            .iter()
+            .find(|b| b.end_time.is_none())
+            .map(|b| b.start_time)
+    }
+
+    /// The project followed by the tags, e.g. `acme +dev +review`.
+    pub fn label(&self) -> String {
+        let tags: String = self.tags.iter().map(|t| format!(" +{}", t)).collect();
+        format!("{}{}", self.project, tags)
     }
 
+    /// Format a duration in the configured style (`1h 5m` by default).
     pub fn format_duration(d: Duration) -> String {
+        match DURATION_FORMAT.get().copied().unwrap_or_default() {
+            DurationFormat::Default => {}
+            DurationFormat::Hm => {
+                let mins = d.num_minutes();
+                return format!("{}:{:02}", mins / 60, mins % 60);
+            }
+            DurationFormat::Decimal => {
+                return format!("{:.2}h", d.num_seconds() as f64 / 3600.0);
+            }
+        }
+
         let hours = d.num_hours();
         let mins = d.num_minutes() % 60;
         let secs = d.num_seconds() % 60;
@@ -31,6 +121,39 @@ impl Frame {
     }
 }
 
+/// How `Frame::format_duration` prints durations.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DurationFormat {
+    /// `1h 5m`, `5m 30s` or `30s`
+    #[default]
+    Default,
+    /// `1:05`
+    Hm,
+    /// `1.08h`
+    Decimal,
+}
+
+static DURATION_FORMAT: OnceLock<DurationFormat> = OnceLock::new();
+
+/// Set the duration format for the rest of the process. Only the first call
+/// has any effect.
+pub fn set_duration_format(format: DurationFormat) {
+    let _ = DURATION_FORMAT.set(format);
+}
+
+/// How much of `start..end` falls within `from..to`.
+fn overlap(
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+) -> Duration {
+    end.min(to)
+        .signed_duration_since(start.max(from))
+        .max(Duration::zero())
+}
+
 /// Safely convert a Unix timestamp to DateTime<Local>.
 /// Uses earliest() to handle DST ambiguity, falls back to UTC interpretation.
 pub fn timestamp_to_local(ts: i64) -> DateTime<Local> {
@@ -47,62 +170,474 @@ pub fn timestamp_to_local(ts: i64) -> DateTime<Local> {
     }
 }
 
+/// SQL expression for a frame's tags as a JSON array, in the order they were given.
+const TAGS_SQL: &str = "(SELECT json_group_array(tag) FROM \
+     (SELECT tag FROM frame_tags WHERE frame_id = frames.id ORDER BY position))";
+
+/// SQL expression for a frame's breaks as a JSON array of `[start, end]` pairs.
+const BREAKS_SQL: &str = "(SELECT json_group_array(json_array(start_time, end_time)) FROM \
+     (SELECT start_time, end_time FROM frame_breaks WHERE frame_id = frames.id ORDER BY start_time))";
+
+/// Sort order for [`FrameQuery`] results.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Order {
+    #[default]
+    StartAsc,
+    StartDesc,
+    EndDesc,
+}
+
+/// Project and tag filters. Patterns use SQLite GLOB syntax (`*`, `?`,
+/// `[abc]`) and are case-sensitive; project patterns also match sub-projects.
+/// A frame matches if its project matches any of `projects` and any of its
+/// tags matches any of `tags`; the exclude lists remove frames matching any
+/// of their patterns. Empty lists don't filter.
+#[derive(Clone, Debug, Default)]
+pub struct Filter {
+    pub projects: Vec<String>,
+    pub tags: Vec<String>,
+    pub exclude_projects: Vec<String>,
+    pub exclude_tags: Vec<String>,
+}
+
+/// Which frames to load. Every command reads frames through this, so a
+/// filter behaves the same way everywhere.
+#[derive(Clone, Debug, Default)]
+pub struct FrameQuery {
+    id: Option<i64>,
+    uuid: Option<String>,
+    range: Option<DateRange>,
+    starts_in: Option<DateRange>,
+    project: Option<String>,
+    filter: Filter,
+    open: Option<bool>,
+    order: Order,
+    limit: Option<u32>,
+}
+
+impl FrameQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn id(mut self, id: i64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    pub fn uuid(mut self, uuid: &str) -> Self {
+        self.uuid = Some(uuid.to_string());
+        self
+    }
+
+    /// Frames overlapping `range`, including ones that start before it or
+    /// are still running. `None` means all time.
+    pub fn range(mut self, range: Option<DateRange>) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Frames that start inside `range`, leaving out ones that only overlap
+    /// it. For bulk changes, where a frame from the day before must not be
+    /// caught by `--day`.
+    pub fn starts_in(mut self, range: DateRange) -> Self {
+        self.starts_in = Some(range);
+        self
+    }
+
+    /// Frames on exactly this project (no glob matching).
+    pub fn project(mut self, project: &str) -> Self {
+        self.project = Some(project.to_string());
+        self
+    }
+
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Only running (`true`) or only stopped (`false`) frames.
+    pub fn open(mut self, open: bool) -> Self {
+        self.open = Some(open);
+        self
+    }
+
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn fetch(&self, conn: &Connection) -> Result<Vec<Frame>> {
+        let mut conditions = Vec::new();
+        let mut values: Vec<Value> = Vec::new();
+
+        if let Some(id) = self.id {
+            conditions.push("id = ?".to_string());
+            values.push(id.into());
+        }
+        if let Some(ref uuid) = self.uuid {
+            conditions.push("uuid = ?".to_string());
+            values.push(uuid.clone().into());
+        }
+        if let Some(range) = self.range {
+            let (from, to) = range.bounds();
+            conditions.push(
+                "start_time < ? AND (start_time >= ? OR end_time IS NULL OR end_time > ?)"
+                    .to_string(),
+            );
+            values.push(to.timestamp().into());
+            values.push(from.timestamp().into());
+            values.push(from.timestamp().into());
+        }
+        if let Some(range) = self.starts_in {
+            let (from, to) = range.bounds();
+            conditions.push("start_time >= ? AND start_time < ?".to_string());
+            values.push(from.timestamp().into());
+            values.push(to.timestamp().into());
+        }
+        if let Some(ref project) = self.project {
+            conditions.push("project = ?".to_string());
+            values.push(project.clone().into());
+        }
+        let any = |column: &str, count: usize| {
+            vec![format!("{column} GLOB ?"); count].join(" OR ")
+        };
+        let has_tag = |count: usize| {
+            format!(
+                "EXISTS (SELECT 1 FROM frame_tags WHERE frame_id = frames.id AND ({}))",
+                any("tag", count)
+            )
+        };
+        // A project pattern also matches everything below it: `acme`
+        // includes `acme/backend`.
+        let in_projects = |count: usize| {
+            vec!["project GLOB ? OR project GLOB ? || '/*'"; count].join(" OR ")
+        };
+        let twice = |patterns: &[String]| -> Vec<Value> {
+            patterns.iter().flat_map(|p| [p.clone().into(), p.clone().into()]).collect()
+        };
+        let f = &self.filter;
+        if !f.projects.is_empty() {
+            conditions.push(format!("({})", in_projects(f.projects.len())));
+            values.extend(twice(&f.projects));
+        }
+        if !f.exclude_projects.is_empty() {
+            conditions.push(format!("NOT ({})", in_projects(f.exclude_projects.len())));
+            values.extend(twice(&f.exclude_projects));
+        }
+        if !f.tags.is_empty() {
+            conditions.push(has_tag(f.tags.len()));
+            values.extend(f.tags.iter().cloned().map(Value::from));
+        }
+        if !f.exclude_tags.is_empty() {
+            conditions.push(format!("NOT {}", has_tag(f.exclude_tags.len())));
+            values.extend(f.exclude_tags.iter().cloned().map(Value::from));
+        }
+        match self.open {
+            Some(true) => conditions.push("end_time IS NULL".to_string()),
+            Some(false) => conditions.push("end_time IS NOT NULL".to_string()),
+            None => {}
+        }
+
+        let mut sql = format!(
+            "SELECT id, project, start_time, end_time, {TAGS_SQL}, notes, {BREAKS_SQL}, \
+             uuid, created_at, updated_at, COALESCE(billable, 1) FROM frames"
+        );
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(match self.order {
+            Order::StartAsc => " ORDER BY start_time, id",
+            Order::StartDesc => " ORDER BY start_time DESC, id DESC",
+            Order::EndDesc => " ORDER BY end_time DESC, id DESC",
+        });
+        if let Some(limit) = self.limit {
+            sql.push_str(&format!(" LIMIT {limit}"));
+        }
+
+        let mut stmt = conn.prepare(&sql)?;
+        let frames = stmt
+            .query_map(params_from_iter(values), row_to_frame)?
+            .collect::<Result<Vec<_>, _>>()
+            .context("failed to query frames")?;
+        Ok(frames)
+    }
+
+    /// The first matching frame, if any.
+    pub fn fetch_one(&self, conn: &Connection) -> Result<Option<Frame>> {
+        Ok(self.clone().limit(1).fetch(conn)?.into_iter().next())
+    }
+}
+
+fn row_to_frame(row: &rusqlite::Row) -> rusqlite::Result<Frame> {
+    let id: i64 = row.get(0)?;
+    let project: String = row.get(1)?;
+    let start_ts: i64 = row.get(2)?;
+    let end_ts: Option<i64> = row.get(3)?;
+    let tags_json: Option<String> = row.get(4)?;
+    let notes: Option<String> = row.get(5)?;
+    let breaks_json: Option<String> = row.get(6)?;
+    let uuid: String = row.get(7)?;
+    let created_ts: i64 = row.get(8)?;
+    let updated_ts: i64 = row.get(9)?;
+    let billable: bool = row.get(10)?;
+
+    Ok(Frame {
+        id,
+        uuid,
+        project,
+        start_time: timestamp_to_local(start_ts),
+        end_time: end_ts.map(timestamp_to_local),
+        tags: parse_tags(tags_json),
+        notes,
+        breaks: parse_breaks(breaks_json),
+        billable,
+        created_at: timestamp_to_local(created_ts),
+        updated_at: timestamp_to_local(updated_ts),
+    })
+}
+
+fn parse_tags(json: Option<String>) -> Vec<String> {
+    json.and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn parse_breaks(json: Option<String>) -> Vec<Break> {
+    json.and_then(|s| serde_json::from_str::<Vec<(i64, Option<i64>)>>(&s).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(start, end)| Break {
+            start_time: timestamp_to_local(start),
+            end_time: end.map(timestamp_to_local),
+        })
+        .collect()
+}
+
 pub fn get_current(conn: &Connection) -> Result<Option<Frame>> {
-    conn.query_row(
-        "SELECT id, project, start_time, end_time, tags FROM frames WHERE end_time IS NULL",
-        [],
-        |row| {
-            let id: i64 = row.get(0)?;
-            let project: String = row.get(1)?;
-            let start_ts: i64 = row.get(2)?;
-            let end_ts: Option<i64> = row.get(3)?;
-            let tags_str: Option<String> = row.get(4)?;
-
-            Ok(Frame {
-                id,
-                project,
-                start_time: timestamp_to_local(start_ts),
-                end_time: end_ts.map(timestamp_to_local),
-                tags: tags_str
-                    .map(|s| s.split(',').map(String::from).collect())
-                    .unwrap_or_default(),
-            })
-        },
-    )
-    .optional()
-    .context("failed to query current frame")
+    FrameQuery::new()
+        .open(true)
+        .fetch_one(conn)
+        .context("failed to query current frame")
 }
 
-pub fn start(conn: &Connection, project: &str, tags: &[String]) -> Result<Frame> {
-    let now = Local::now();
-    let tags_str = if tags.is_empty() {
-        None
-    } else {
-        Some(tags.join(","))
-    };
+/// Start a frame. Without `billable` it gets the project's default.
+pub fn start(
+    conn: &Connection,
+    project: &str,
+    tags: &[String],
+    notes: Option<&str>,
+    billable: Option<bool>,
+    at: DateTime<Local>,
+) -> Result<Frame> {
+    conn.execute(
+        "INSERT INTO frames (project, start_time, notes, billable) VALUES (?1, ?2, ?3, ?4)",
+        params![project, at.timestamp(), notes, billable],
+    )
+    .map_err(|e| match e {
+        // Another process (e.g. TimerBar) started a frame first
+        rusqlite::Error::SqliteFailure(ref err, Some(ref msg))
+            if err.code == ErrorCode::ConstraintViolation
+                && msg.contains("idx_frames_single_open") =>
+        {
+            anyhow::anyhow!("already tracking - another frame was started concurrently")
+        }
+        e => anyhow::Error::new(e).context("failed to insert frame"),
+    })?;
 
+    let id = conn.last_insert_rowid();
+    set_tags(conn, id, tags)?;
+    // Read it back for the uuid and timestamps filled in by the database
+    FrameQuery::new()
+        .id(id)
+        .fetch_one(conn)?
+        .context("started frame not found")
+}
+
+/// Insert a finished frame. Paused time is recorded as one break ending
+/// with the frame.
+pub fn insert_stopped(
+    conn: &Connection,
+    project: &str,
+    tags: &[String],
+    notes: Option<&str>,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    paused: Duration,
+) -> Result<i64> {
     conn.execute(
-        "INSERT INTO frames (project, start_time, tags) VALUES (?1, ?2, ?3)",
-        params![project, now.timestamp(), tags_str],
+        "INSERT INTO frames (project, start_time, end_time, notes) VALUES (?1, ?2, ?3, ?4)",
+        params![project, start.timestamp(), end.timestamp(), notes],
     )
     .context("failed to insert frame")?;
 
     let id = conn.last_insert_rowid();
-    Ok(Frame {
-        id,
-        project: project.to_string(),
-        start_time: now,
-        end_time: None,
-        tags: tags.to_vec(),
-    })
+    set_tags(conn, id, tags)?;
+    set_paused(conn, id, end, paused)?;
+    Ok(id)
 }
 
-pub fn stop(conn: &Connection, frame_id: i64) -> Result<()> {
-    let now = Local::now();
+/// Overwrite a finished frame with new values, replacing its tags and
+/// breaks as [`insert_stopped`] would create them.
+#[allow(clippy::too_many_arguments)]
+pub fn replace_stopped(
+    conn: &Connection,
+    id: i64,
+    project: &str,
+    tags: &[String],
+    notes: Option<&str>,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    paused: Duration,
+) -> Result<()> {
     conn.execute(
-        "UPDATE frames SET end_time = ?1 WHERE id = ?2",
-        params![now.timestamp(), frame_id],
+        "UPDATE frames SET project = ?1, start_time = ?2, end_time = ?3, notes = ?4 WHERE id = ?5",
+        params![project, start.timestamp(), end.timestamp(), notes, id],
+    )
+    .context("failed to update frame")?;
+    set_tags(conn, id, tags)?;
+    conn.execute("DELETE FROM frame_breaks WHERE frame_id = ?1", [id])
+        .context("failed to clear breaks")?;
+    set_paused(conn, id, end, paused)
+}
+
+/// Record `paused` as one break ending at `end`.
+fn set_paused(conn: &Connection, id: i64, end: DateTime<Local>, paused: Duration) -> Result<()> {
+    if paused > Duration::zero() {
+        conn.execute(
+            "INSERT INTO frame_breaks (frame_id, start_time, end_time)
+             SELECT id, MAX(start_time, ?2), ?3 FROM frames WHERE id = ?1",
+            params![id, (end - paused).timestamp(), end.timestamp()],
+        )
+        .context("failed to insert break")?;
+    }
+    Ok(())
+}
+
+pub fn set_billable(conn: &Connection, id: i64, billable: bool) -> Result<()> {
+    conn.execute("UPDATE frames SET billable = ?1 WHERE id = ?2", params![billable, id])
+        .context("failed to update frame")?;
+    Ok(())
+}
+
+/// Set the identity and timestamps kept with a frame from another database.
+/// Must run after any change to its tags or breaks, which bump `updated_at`.
+pub fn set_metadata(
+    conn: &Connection,
+    id: i64,
+    uuid: Option<&str>,
+    created_at: Option<DateTime<Local>>,
+    updated_at: Option<DateTime<Local>>,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE frames SET uuid = COALESCE(?1, uuid), created_at = COALESCE(?2, created_at),
+                           updated_at = COALESCE(?3, updated_at)
+         WHERE id = ?4",
+        params![
+            uuid,
+            created_at.map(|t| t.timestamp()),
+            updated_at.map(|t| t.timestamp()),
+            id
+        ],
+    )
+    .context("failed to set frame metadata")?;
+    Ok(())
+}
+
+/// Whether a frame on `project` with exactly these times exists.
+pub fn exists(
+    conn: &Connection,
+    project: &str,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM frames WHERE project = ?1 AND start_time = ?2 AND end_time = ?3)",
+        params![project, start.timestamp(), end.timestamp()],
+        |row| row.get(0),
+    )
+    .context("failed to look for duplicate frame")
+}
+
+/// The latest-ending closed frame that ends after `at`, if any. A frame
+/// starting at `at` would overlap it.
+pub fn ending_after(conn: &Connection, at: DateTime<Local>) -> Result<Option<(i64, DateTime<Local>)>> {
+    conn.query_row(
+        "SELECT id, end_time FROM frames WHERE end_time > ?1 ORDER BY end_time DESC LIMIT 1",
+        [at.timestamp()],
+        |row| Ok((row.get(0)?, timestamp_to_local(row.get(1)?))),
+    )
+    .optional()
+    .context("failed to check for overlapping frames")
+}
+
+/// Replace a frame's tags, preserving their order.
+pub fn set_tags(conn: &Connection, frame_id: i64, tags: &[String]) -> Result<()> {
+    conn.execute("DELETE FROM frame_tags WHERE frame_id = ?1", [frame_id])
+        .context("failed to clear tags")?;
+
+    let mut stmt = conn.prepare(
+        "INSERT OR IGNORE INTO frame_tags (frame_id, tag, position) VALUES (?1, ?2, ?3)",
+    )?;
+    for (position, tag) in tags.iter().enumerate() {
+        stmt.execute(params![frame_id, tag, position as i64])
+            .context("failed to insert tag")?;
+    }
+    Ok(())
+}
+
+/// Close a frame, ending any pause in progress. A given note replaces any
+/// note set when the frame started.
+pub fn stop(
+    conn: &Connection,
+    frame_id: i64,
+    notes: Option<&str>,
+    at: DateTime<Local>,
+) -> Result<()> {
+    // Ending before a pause began would leave a break that ends before it starts
+    let paused_at: Option<i64> = conn.query_row(
+        "SELECT MAX(start_time) FROM frame_breaks WHERE frame_id = ?1",
+        [frame_id],
+        |row| row.get(0),
+    )?;
+    if let Some(paused_at) = paused_at.filter(|t| at.timestamp() < *t) {
+        anyhow::bail!(
+            "stop time is before the frame was paused ({})",
+            timestamp_to_local(paused_at).format("%Y-%m-%d %H:%M")
+        );
+    }
+    conn.execute(
+        "UPDATE frame_breaks SET end_time = ?1 WHERE frame_id = ?2 AND end_time IS NULL",
+        params![at.timestamp(), frame_id],
+    )
+    .context("failed to end pause")?;
+    conn.execute(
+        "UPDATE frames SET end_time = ?1, notes = COALESCE(?3, notes) WHERE id = ?2",
+        params![at.timestamp(), frame_id, notes],
     )
     .context("failed to stop frame")?;
     Ok(())
 }
+
+pub fn pause(conn: &Connection, frame_id: i64, at: DateTime<Local>) -> Result<()> {
+    conn.execute(
+        "INSERT INTO frame_breaks (frame_id, start_time) VALUES (?1, ?2)",
+        params![frame_id, at.timestamp()],
+    )
+    .context("failed to pause frame")?;
+    Ok(())
+}
+
+pub fn resume(conn: &Connection, frame_id: i64, at: DateTime<Local>) -> Result<()> {
+    conn.execute(
+        "UPDATE frame_breaks SET end_time = ?1 WHERE frame_id = ?2 AND end_time IS NULL",
+        params![at.timestamp(), frame_id],
+    )
+    .context("failed to resume frame")?;
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use chrono::{Duration, NaiveDate, Weekday};
+use std::collections::BTreeMap;
+
+use crate::frame::Frame;
+use crate::time::{week_start, DateRange, Period};
+
+/// A timesheet: projects against consecutive days, weeks or months.
+pub struct Grid {
+    pub period: Period,
+    /// Column periods, oldest first
+    pub columns: Vec<DateRange>,
+    /// One row per project, sorted by name, with a cell per column
+    pub rows: Vec<(String, Vec<Duration>)>,
+}
+
+impl Grid {
+    /// Build the grid for `range`, splitting frames at midnight. Without a
+    /// range the columns span the days that have any tracked time.
+    pub fn build(frames: &[Frame], range: Option<DateRange>, period: Period) -> Self {
+        let mut days: BTreeMap<&str, BTreeMap<NaiveDate, Duration>> = BTreeMap::new();
+        for frame in frames {
+            for (date, duration) in frame.daily_durations() {
+                let in_range = range.is_none_or(|r| r.start <= date && date <= r.end);
+                if in_range && !duration.is_zero() {
+                    *days
+                        .entry(&frame.project)
+                        .or_default()
+                        .entry(date)
+                        .or_insert(Duration::zero()) += duration;
+                }
+            }
+        }
+
+        let span = range.or_else(|| {
+            let dates = days.values().flat_map(|d| d.keys().copied());
+            let first = dates.clone().min()?;
+            Some(DateRange { start: first, end: dates.max()? })
+        });
+        let mut columns = Vec::new();
+        if let Some(span) = span {
+            let mut date = span.start;
+            while date <= span.end {
+                let column = DateRange::period(period, date);
+                date = column.end + Duration::days(1);
+                columns.push(column);
+            }
+        }
+
+        let rows = days
+            .into_iter()
+            .map(|(project, days)| {
+                let cells = columns
+                    .iter()
+                    .map(|c| days.range(c.start..=c.end).map(|(_, d)| *d).sum())
+                    .collect();
+                (project.to_string(), cells)
+            })
+            .collect();
+
+        Self { period, columns, rows }
+    }
+
+    /// Stable column key: `2024-01-15`, `2024-W03` or `2024-01`. Weeks
+    /// that don't start on Monday are keyed by their first day instead.
+    pub fn key(&self, column: &DateRange) -> String {
+        match self.period {
+            Period::Day => column.start.format("%Y-%m-%d").to_string(),
+            Period::Week if week_start() == Weekday::Mon => column.start.format("%G-W%V").to_string(),
+            Period::Week => column.start.format("%Y-%m-%d").to_string(),
+            Period::Month => column.start.format("%Y-%m").to_string(),
+            Period::Year => column.start.format("%Y").to_string(),
+        }
+    }
+
+    /// Total for each column across all projects.
+    pub fn column_totals(&self) -> Vec<Duration> {
+        (0..self.columns.len())
+            .map(|i| self.rows.iter().map(|(_, cells)| cells[i]).sum())
+            .collect()
+    }
+
+    pub fn total(&self) -> Duration {
+        self.column_totals().into_iter().sum()
+    }
+}
@@ -0,0 +1,362 @@
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::output::FrameRecord;
+
+/// A frame read from an import file.
+#[derive(Debug)]
+pub struct ImportFrame {
+    pub project: String,
+    pub start_time: DateTime<Local>,
+    /// `None` for a frame that was still running when exported
+    pub end_time: Option<DateTime<Local>>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+    /// Time spent paused; imported as one break at the end of the frame
+    pub paused: Duration,
+    /// Identity from the source database, matched against existing frames
+    pub uuid: Option<String>,
+    pub created_at: Option<DateTime<Local>>,
+    /// Decides which side wins when both databases have the frame
+    pub updated_at: Option<DateTime<Local>>,
+    /// `None` gives the frame its project's default
+    pub billable: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum ImportFormat {
+    /// `timer export --format json`
+    TimerJson,
+    /// `timer export --format csv`
+    TimerCsv,
+    /// Watson's `frames` file, or `watson log --json`
+    Watson,
+    /// Toggl Track detailed report CSV
+    TogglCsv,
+}
+
+impl ImportFormat {
+    /// Guess the format from the file contents.
+    pub fn detect(text: &str) -> Result<Self> {
+        let text = text.trim_start();
+        if text.starts_with('[') {
+            // Watson's frames file is an array of arrays and `watson log
+            // --json` uses "start"/"stop"; our export has "start_time".
+            let first: Option<Value> = serde_json::from_str::<Vec<Value>>(text)
+                .context("invalid JSON")?
+                .into_iter()
+                .next();
+            return Ok(match first {
+                Some(Value::Object(map)) if !map.contains_key("start_time") => ImportFormat::Watson,
+                Some(Value::Array(_)) => ImportFormat::Watson,
+                _ => ImportFormat::TimerJson,
+            });
+        }
+
+        let header = text.lines().next().unwrap_or_default().to_lowercase();
+        if header.contains("start date") {
+            Ok(ImportFormat::TogglCsv)
+        } else if header.contains("start_time") {
+            Ok(ImportFormat::TimerCsv)
+        } else {
+            bail!("could not detect the file format - use --from-format")
+        }
+    }
+}
+
+/// Read every frame in `text`. Fails on the first malformed entry.
+pub fn parse(format: ImportFormat, text: &str) -> Result<Vec<ImportFrame>> {
+    match format {
+        ImportFormat::TimerJson => {
+            let records: Vec<FrameRecord> = serde_json::from_str(text).context("invalid JSON")?;
+            records
+                .into_iter()
+                .enumerate()
+                .map(|(i, r)| from_record(r).with_context(|| format!("frame {}", i + 1)))
+                .collect()
+        }
+        ImportFormat::TimerCsv => csv_rows(text)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| timer_csv(&row).with_context(|| format!("row {}", i + 2)))
+            .collect(),
+        ImportFormat::Watson => {
+            let entries: Vec<Value> = serde_json::from_str(text).context("invalid JSON")?;
+            entries
+                .iter()
+                .enumerate()
+                .map(|(i, e)| watson(e).with_context(|| format!("frame {}", i + 1)))
+                .collect()
+        }
+        ImportFormat::TogglCsv => csv_rows(text)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, row)| toggl_csv(&row).with_context(|| format!("row {}", i + 2)))
+            .collect(),
+    }
+}
+
+fn from_record(r: FrameRecord) -> Result<ImportFrame> {
+    let start_time = parse_local(&r.start_time)?;
+    let end_time = r.end_time.as_deref().map(parse_local).transpose()?;
+    // Exports from before frames had UUIDs leave these empty
+    let optional_time = |s: &str| Some(s).filter(|s| !s.is_empty()).map(parse_local).transpose();
+    Ok(ImportFrame {
+        uuid: Some(r.uuid.to_lowercase()).filter(|u| !u.is_empty()),
+        created_at: optional_time(&r.created_at)?,
+        updated_at: optional_time(&r.updated_at)?,
+        billable: r.billable,
+        ..frame(
+            r.project,
+            start_time,
+            end_time,
+            r.tags,
+            r.notes,
+            Duration::seconds(r.paused_seconds),
+        )?
+    })
+}
+
+fn timer_csv(row: &HashMap<String, String>) -> Result<ImportFrame> {
+    let get = |name: &str| row.get(name).map(String::as_str).unwrap_or_default();
+    let optional = |name: &str| Some(get(name).to_string()).filter(|s| !s.is_empty());
+
+    from_record(FrameRecord {
+        project: get("project").to_string(),
+        start_time: get("start_time").to_string(),
+        end_time: optional("end_time"),
+        tags: split_joined_tags(get("tags")),
+        paused_seconds: optional("paused_seconds")
+            .map(|s| s.parse())
+            .transpose()
+            .context("invalid paused_seconds")?
+            .unwrap_or(0),
+        notes: optional("notes"),
+        uuid: get("uuid").to_string(),
+        created_at: get("created_at").to_string(),
+        updated_at: get("updated_at").to_string(),
+        billable: optional("billable")
+            .map(|s| s.parse())
+            .transpose()
+            .context("invalid billable")?,
+        ..Default::default()
+    })
+}
+
+/// Either a frames-file entry `[start, stop, project, id, tags, updated]`
+/// with Unix timestamps, or a `watson log --json` object. Watson's frame ids
+/// are UUIDs without dashes.
+fn watson(entry: &Value) -> Result<ImportFrame> {
+    let tags = |v: Option<&Value>| -> Vec<String> {
+        v.and_then(Value::as_array)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(Value::as_str)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    match entry {
+        Value::Array(fields) => {
+            let timestamp = |i: usize| -> Result<DateTime<Local>> {
+                let ts = fields
+                    .get(i)
+                    .and_then(Value::as_i64)
+                    .context("missing timestamp")?;
+                Local
+                    .timestamp_opt(ts, 0)
+                    .single()
+                    .context("invalid timestamp")
+            };
+            let project = fields
+                .get(2)
+                .and_then(Value::as_str)
+                .context("missing project")?;
+            Ok(ImportFrame {
+                uuid: watson_uuid(fields.get(3)),
+                updated_at: fields.get(5).map(|_| timestamp(5)).transpose()?,
+                ..frame(
+                    project.to_string(),
+                    timestamp(0)?,
+                    Some(timestamp(1)?),
+                    tags(fields.get(4)),
+                    None,
+                    Duration::zero(),
+                )?
+            })
+        }
+        Value::Object(map) => {
+            let time = |key: &str| -> Result<DateTime<Local>> {
+                let s = map
+                    .get(key)
+                    .and_then(Value::as_str)
+                    .with_context(|| format!("missing {key}"))?;
+                Ok(DateTime::parse_from_rfc3339(s)
+                    .with_context(|| format!("invalid {key}: {s}"))?
+                    .with_timezone(&Local))
+            };
+            let project = map
+                .get("project")
+                .and_then(Value::as_str)
+                .context("missing project")?;
+            Ok(ImportFrame {
+                uuid: watson_uuid(map.get("id")),
+                ..frame(
+                    project.to_string(),
+                    time("start")?,
+                    Some(time("stop")?),
+                    tags(map.get("tags")),
+                    None,
+                    Duration::zero(),
+                )?
+            })
+        }
+        _ => bail!("expected an array or object"),
+    }
+}
+
+/// A row of Toggl's detailed report. The description becomes the note and
+/// entries without a project go to "(no project)".
+fn toggl_csv(row: &HashMap<String, String>) -> Result<ImportFrame> {
+    let get = |name: &str| row.get(name).map(String::as_str).unwrap_or_default();
+    let datetime = |date: &str, time: &str| -> Result<DateTime<Local>> {
+        let date = NaiveDate::parse_from_str(get(date), "%Y-%m-%d")
+            .with_context(|| format!("invalid {date}: {}", get(date)))?;
+        let time = NaiveTime::parse_from_str(get(time), "%H:%M:%S")
+            .with_context(|| format!("invalid {time}: {}", get(time)))?;
+        local(date.and_time(time))
+    };
+
+    let project = match get("project") {
+        "" => "(no project)",
+        p => p,
+    };
+    let notes = Some(get("description").to_string()).filter(|s| !s.is_empty());
+    let billable = match get("billable").to_lowercase().as_str() {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    };
+    Ok(ImportFrame {
+        billable,
+        ..frame(
+            project.to_string(),
+            datetime("start date", "start time")?,
+            Some(datetime("end date", "end time")?),
+            split_tags(get("tags"), ','),
+            notes,
+            Duration::zero(),
+        )?
+    })
+}
+
+fn frame(
+    project: String,
+    start_time: DateTime<Local>,
+    end_time: Option<DateTime<Local>>,
+    tags: Vec<String>,
+    notes: Option<String>,
+    paused: Duration,
+) -> Result<ImportFrame> {
+    if project.is_empty() {
+        bail!("missing project");
+    }
+    if end_time.is_some_and(|end| end < start_time) {
+        bail!("ends before it starts");
+    }
+    Ok(ImportFrame {
+        project,
+        start_time,
+        end_time,
+        tags,
+        notes,
+        paused,
+        uuid: None,
+        created_at: None,
+        updated_at: None,
+        billable: None,
+    })
+}
+
+/// `0f1e...` (32 hex digits) as `0f1e....-....-....-....-............`.
+fn watson_uuid(id: Option<&Value>) -> Option<String> {
+    let hex = id?.as_str()?.to_lowercase();
+    if hex.len() != 32 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Rows keyed by lowercased header name.
+fn csv_rows(text: &str) -> Result<Vec<HashMap<String, String>>> {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .context("invalid CSV header")?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record.context("invalid CSV")?;
+            Ok(headers
+                .iter()
+                .cloned()
+                .zip(record.iter().map(String::from))
+                .collect())
+        })
+        .collect()
+}
+
+fn split_tags(s: &str, separator: char) -> Vec<String> {
+    s.split(separator)
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Undo [`output::join_tags`](crate::output::join_tags): split on commas
+/// that aren't escaped with a backslash.
+fn split_joined_tags(s: &str) -> Vec<String> {
+    let mut tags = vec![String::new()];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => tags.last_mut().unwrap().extend(chars.next()),
+            ',' => tags.push(String::new()),
+            c => tags.last_mut().unwrap().push(c),
+        }
+    }
+    tags.into_iter()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+/// Parse the exporter's `YYYY-MM-DDTHH:MM:SS` local time.
+fn parse_local(s: &str) -> Result<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S")
+        .with_context(|| format!("invalid time: {s}"))?;
+    local(naive)
+}
+
+fn local(naive: NaiveDateTime) -> Result<DateTime<Local>> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .with_context(|| format!("{naive} doesn't exist in local time"))
+}
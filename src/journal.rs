@@ -0,0 +1,257 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::frame::{self, timestamp_to_local, Frame};
+
+/// Operations kept for `undo`; older ones are dropped as new ones are added.
+const MAX_OPERATIONS: i64 = 100;
+
+/// A part of a frame an operation changes. `undo` puts back only these, so
+/// later changes to the rest of the frame (like stopping it) are kept.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Field {
+    Project,
+    Tags,
+    /// Start and end time, and the pauses in between
+    Times,
+    Notes,
+    Billable,
+}
+
+/// A frame as it was before an operation, with everything needed to put it
+/// back.
+#[derive(Deserialize, Serialize)]
+struct Snapshot {
+    id: i64,
+    uuid: String,
+    project: String,
+    start_time: i64,
+    end_time: Option<i64>,
+    notes: Option<String>,
+    /// Missing from entries journaled before frames had the flag
+    #[serde(default = "billable_default")]
+    billable: bool,
+    created_at: i64,
+    tags: Vec<String>,
+    breaks: Vec<(i64, Option<i64>)>,
+    /// What the operation changed; missing means everything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    changed: Option<Vec<Field>>,
+}
+
+impl From<&Frame> for Snapshot {
+    fn from(frame: &Frame) -> Self {
+        Self {
+            id: frame.id,
+            uuid: frame.uuid.clone(),
+            project: frame.project.clone(),
+            start_time: frame.start_time.timestamp(),
+            end_time: frame.end_time.map(|t| t.timestamp()),
+            notes: frame.notes.clone(),
+            billable: frame.billable,
+            created_at: frame.created_at.timestamp(),
+            tags: frame.tags.clone(),
+            breaks: frame
+                .breaks
+                .iter()
+                .map(|b| (b.start_time.timestamp(), b.end_time.map(|t| t.timestamp())))
+                .collect(),
+            changed: None,
+        }
+    }
+}
+
+fn billable_default() -> bool {
+    true
+}
+
+/// A journaled change that `undo` can reverse.
+pub struct Operation {
+    pub id: i64,
+    pub created_at: DateTime<Local>,
+    pub description: String,
+    frames: Vec<Snapshot>,
+}
+
+/// Save the before-image of `frames`, which `description` is about to
+/// replace or delete. Call it in the same transaction as the change.
+pub fn record(conn: &Connection, description: &str, frames: &[Frame]) -> Result<()> {
+    save(conn, description, frames, None)
+}
+
+/// Like [`record`], for an operation that only changes `fields`.
+pub fn record_fields(
+    conn: &Connection,
+    description: &str,
+    frames: &[Frame],
+    fields: &[Field],
+) -> Result<()> {
+    save(conn, description, frames, Some(fields))
+}
+
+fn save(conn: &Connection, description: &str, frames: &[Frame], fields: Option<&[Field]>) -> Result<()> {
+    let snapshots: Vec<Snapshot> = frames
+        .iter()
+        .map(|frame| Snapshot {
+            changed: fields.map(<[Field]>::to_vec),
+            ..Snapshot::from(frame)
+        })
+        .collect();
+    conn.execute(
+        "INSERT INTO operations (created_at, description, frames) VALUES (?1, ?2, ?3)",
+        params![Local::now().timestamp(), description, serde_json::to_string(&snapshots)?],
+    )
+    .context("failed to record operation")?;
+    conn.execute(
+        "DELETE FROM operations WHERE id <= (SELECT MAX(id) FROM operations) - ?1",
+        [MAX_OPERATIONS],
+    )?;
+    Ok(())
+}
+
+/// Journaled operations, most recent first.
+pub fn recent(conn: &Connection, limit: usize) -> Result<Vec<Operation>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, created_at, description, frames FROM operations ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = stmt
+        .query_map([limit as i64], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get::<_, String>(3)?))
+        })?
+        .collect::<Result<Vec<(i64, i64, String, String)>, _>>()
+        .context("failed to read operations")?;
+
+    rows.into_iter()
+        .map(|(id, created_at, description, frames)| {
+            Ok(Operation {
+                id,
+                created_at: timestamp_to_local(created_at),
+                frames: serde_json::from_str(&frames)
+                    .with_context(|| format!("invalid journal entry {}", id))?,
+                description,
+            })
+        })
+        .collect()
+}
+
+/// Put the frames of `op` back as they were and drop it from the journal.
+/// Returns notes about frames that changed since in ways undo leaves alone.
+pub fn undo(conn: &Connection, op: &Operation) -> Result<Vec<String>> {
+    let mut notes = Vec::new();
+    for snapshot in &op.frames {
+        let note = restore(conn, snapshot)
+            .with_context(|| format!("failed to undo {}", op.description))?;
+        notes.extend(note);
+    }
+    conn.execute("DELETE FROM operations WHERE id = ?1", [op.id])?;
+    Ok(notes)
+}
+
+fn restore(conn: &Connection, s: &Snapshot) -> Result<Option<String>> {
+    // A deleted frame's id may have been reused since, so find it by uuid
+    let existing: Option<(i64, Option<i64>)> = conn
+        .query_row("SELECT id, end_time FROM frames WHERE uuid = ?1", [&s.uuid], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .optional()?;
+
+    let Some((id, end_time)) = existing else {
+        insert(conn, s)?;
+        return Ok(None);
+    };
+    let changed = |field| s.changed.as_ref().is_none_or(|c| c.contains(&field));
+
+    if changed(Field::Project) {
+        conn.execute("UPDATE frames SET project = ?1 WHERE id = ?2", params![s.project, id])?;
+    }
+    if changed(Field::Notes) {
+        conn.execute("UPDATE frames SET notes = ?1 WHERE id = ?2", params![s.notes, id])?;
+    }
+    if changed(Field::Billable) {
+        frame::set_billable(conn, id, s.billable)?;
+    }
+    if changed(Field::Tags) {
+        frame::set_tags(conn, id, &s.tags)?;
+    }
+    if changed(Field::Times) {
+        conn.execute(
+            "UPDATE frames SET start_time = ?1, end_time = ?2 WHERE id = ?3",
+            params![s.start_time, s.end_time, id],
+        )
+        .map_err(single_open)?;
+        restore_breaks(conn, id, &s.breaks)?;
+    } else if end_time.is_some() != s.end_time.is_some() {
+        let state = if end_time.is_some() { "stopped" } else { "restarted" };
+        return Ok(Some(format!("frame {} was {} since, so it stays {}", id, state, state)));
+    }
+    Ok(None)
+}
+
+/// Bring back a deleted frame, under its old id if that is still free.
+fn insert(conn: &Connection, s: &Snapshot) -> Result<()> {
+    if s.end_time.is_none() && frame::get_current(conn)?.is_some() {
+        bail!("can't restore a running frame while tracking - stop first");
+    }
+    let id_taken: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM frames WHERE id = ?1)",
+        [s.id],
+        |row| row.get(0),
+    )?;
+    conn.execute(
+        "INSERT INTO frames (id, uuid, project, start_time, end_time, notes, billable,
+                             created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            (!id_taken).then_some(s.id),
+            s.uuid,
+            s.project,
+            s.start_time,
+            s.end_time,
+            s.notes,
+            s.billable,
+            s.created_at
+        ],
+    )
+    .map_err(single_open)?;
+    let id = conn.last_insert_rowid();
+    frame::set_tags(conn, id, &s.tags)?;
+    restore_breaks(conn, id, &s.breaks)
+}
+
+fn restore_breaks(conn: &Connection, id: i64, breaks: &[(i64, Option<i64>)]) -> Result<()> {
+    conn.execute("DELETE FROM frame_breaks WHERE frame_id = ?1", [id])?;
+    for (start, end) in breaks {
+        conn.execute(
+            "INSERT INTO frame_breaks (frame_id, start_time, end_time) VALUES (?1, ?2, ?3)",
+            params![id, start, end],
+        )?;
+    }
+    Ok(())
+}
+
+/// Reopening a frame trips the single open frame index if another is running.
+fn single_open(e: rusqlite::Error) -> anyhow::Error {
+    if e.to_string().contains("idx_frames_single_open") {
+        anyhow!("another frame is running - stop it first")
+    } else {
+        e.into()
+    }
+}
+
+/// `frame 12 (acme +dev, 2024-03-01 09:00 - 10:30)`, for descriptions.
+pub fn describe(frame: &Frame) -> String {
+    let end = match frame.end_time {
+        Some(end) => end.format("%H:%M").to_string(),
+        None => "now".to_string(),
+    };
+    format!(
+        "frame {} ({}, {} - {})",
+        frame.id,
+        frame.label(),
+        frame.start_time.format("%Y-%m-%d %H:%M"),
+        end
+    )
+}
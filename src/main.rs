@@ -1,18 +1,41 @@
+mod billable;
+mod budget;
+mod check;
+mod config;
 mod commands;
 mod db;
 mod frame;
 mod git;
+mod grid;
+mod import;
+mod journal;
+mod output;
+mod project;
+mod rate;
+mod rounding;
+mod template;
+mod time;
 
 use anyhow::Result;
-use chrono::NaiveDate;
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Duration, Local};
+use clap::{Args, Parser, Subcommand};
 use clap_complete::Shell;
 
+use config::DefaultRange;
+use frame::Filter;
+use output::Format;
+use rate::RateTarget;
+use rounding::{RoundMode, RoundPer, Rounding};
+use time::{DateRange, Period};
+
 #[derive(Parser)]
 #[command(name = "timer-cli", version, about = "Track your time")]
 pub struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Output format for status, log, report, projects, tags and export
+    #[arg(long, global = true, value_enum, ignore_case = true)]
+    format: Option<Format>,
 }
 
 #[derive(Subcommand)]
@@ -24,73 +47,183 @@ enum Commands {
         /// Tags (prefix with +)
         #[arg(value_parser = parse_tag)]
         tags: Vec<String>,
+        /// Note describing the work
+        #[arg(short = 'm', long)]
+        note: Option<String>,
+        /// Start at this time (HH:MM, yesterday HH:MM or YYYY-MM-DD HH:MM)
+        #[arg(long, value_parser = parse_at, conflicts_with = "ago")]
+        at: Option<DateTime<Local>>,
+        /// Start this long ago (e.g. 20m, 1h30m)
+        #[arg(long, value_parser = parse_ago)]
+        ago: Option<DateTime<Local>>,
+        /// Stop the running frame first, if any
+        #[arg(long, conflicts_with = "no_switch")]
+        switch: bool,
+        /// Fail if a frame is running, even if start_switch is configured
+        #[arg(long)]
+        no_switch: bool,
+        #[command(flatten)]
+        bill: BillArgs,
     },
     /// Stop the current frame
-    Stop,
+    Stop {
+        /// Note describing the work (replaces any note given at start)
+        #[arg(short = 'm', long)]
+        note: Option<String>,
+        /// Stop at this time (HH:MM, yesterday HH:MM or YYYY-MM-DD HH:MM)
+        #[arg(long, value_parser = parse_at, conflicts_with = "ago")]
+        at: Option<DateTime<Local>>,
+        /// Stop this long ago (e.g. 20m, 1h30m)
+        #[arg(long, value_parser = parse_ago)]
+        ago: Option<DateTime<Local>>,
+    },
+    /// Pause the current frame (e.g. for a break)
+    Pause,
+    /// Resume the paused frame
+    Resume,
     /// Show current tracking status
-    Status,
+    Status {
+        /// Custom line, e.g. '{project} {elapsed:hm} {tags}'. Fields: project,
+        /// tags, id, note, start[:strftime], elapsed[:hm|hms|m|s],
+        /// today[:hm|hms|m|s] (project total today) and paused
+        #[arg(long)]
+        template: Option<String>,
+        /// Printed instead of "Not tracking" when nothing is running
+        #[arg(long, default_value = "Not tracking")]
+        not_tracking: String,
+    },
     /// Show recent frames
     Log {
-        /// Start date (YYYY-MM-DD)
-        #[arg(short, long, value_parser = parse_date)]
-        from: Option<NaiveDate>,
-        /// End date (YYYY-MM-DD)
-        #[arg(short, long, value_parser = parse_date)]
-        to: Option<NaiveDate>,
-        /// Show all entries (ignore date range)
-        #[arg(short, long)]
-        all: bool,
+        #[command(flatten)]
+        range: RangeArgs,
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Show frame notes
+        #[arg(long)]
+        notes: bool,
     },
     /// Cancel (delete) the current frame
     Cancel,
-    /// Delete a frame by ID
+    /// Delete a frame by ID, or every stopped frame that starts in a range and
+    /// matches the filters (projects include their sub-projects)
     Delete {
         /// Frame ID to delete
-        id: i64,
+        #[arg(required_unless_present_any = SELECTORS, conflicts_with_all = SELECTORS)]
+        id: Option<i64>,
+        #[command(flatten)]
+        range: RangeArgs,
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Don't ask before deleting matching frames
+        #[arg(short, long)]
+        yes: bool,
     },
     /// List all projects
-    Projects,
+    Projects {
+        /// Show sub-projects (acme/backend) nested under their parent
+        #[arg(long)]
+        tree: bool,
+    },
     /// List all tags
     Tags,
     /// Show time report aggregated by project or tag
     Report {
-        /// Start date (YYYY-MM-DD)
-        #[arg(short, long, value_parser = parse_date)]
-        from: Option<NaiveDate>,
-        /// End date (YYYY-MM-DD)
-        #[arg(short, long, value_parser = parse_date)]
-        to: Option<NaiveDate>,
+        #[command(flatten)]
+        range: RangeArgs,
         /// Group by tag instead of project
         #[arg(long)]
         by_tag: bool,
-        /// Show all entries (ignore date range)
-        #[arg(short, long)]
-        all: bool,
+        /// Break totals down by day, splitting frames at midnight (raw time,
+        /// no rounding)
+        #[arg(long, conflicts_with_all = ["by_tag", "round", "round_mode", "round_per"])]
+        by_day: bool,
+        /// Print a timesheet of projects by day, week or month (raw time, no
+        /// rounding)
+        #[arg(
+            long,
+            value_parser = parse_grid,
+            conflicts_with_all = ["by_tag", "by_day", "round", "round_mode", "round_per"]
+        )]
+        grid: Option<Period>,
+        /// Show earnings per client and project from `timer rate` rates
+        #[arg(long, conflicts_with_all = ["by_tag", "by_day", "grid"])]
+        earnings: bool,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[command(flatten)]
+        round: RoundArgs,
     },
     /// Edit an existing frame
     Edit {
         /// Frame ID to edit
-        id: i64,
+        #[arg(required_unless_present = "conditions", conflicts_with = "conditions")]
+        id: Option<i64>,
+        /// Edit every frame matching KEY=VALUE instead, where KEY is project
+        /// (a pattern that includes sub-projects, like --project), tag, from or
+        /// to (frames starting in that range); repeat to narrow down
+        #[arg(long = "where", value_name = "KEY=VALUE", value_parser = parse_condition)]
+        conditions: Vec<(String, String)>,
         /// New project name
-        #[arg(short, long)]
+        #[arg(short, long, visible_alias = "set-project")]
         project: Option<String>,
         /// New tags (prefix with +)
         #[arg(short, long, value_parser = parse_tag)]
         tags: Option<Vec<String>>,
         /// New start time (HH:MM or YYYY-MM-DD HH:MM)
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "conditions")]
         start: Option<String>,
         /// New end time (HH:MM or YYYY-MM-DD HH:MM)
-        #[arg(short, long)]
+        #[arg(short, long, conflicts_with = "conditions")]
         end: Option<String>,
+        /// New note (empty string clears it)
+        #[arg(short = 'm', long)]
+        note: Option<String>,
+        #[command(flatten)]
+        bill: BillArgs,
+        /// Apply even if the new times overlap another frame
+        #[arg(long)]
+        force: bool,
+        /// Don't ask before editing frames matching --where
+        #[arg(short, long, requires = "conditions")]
+        yes: bool,
     },
     /// Restart the last stopped frame
     Restart,
-    /// Export all frames to JSON or CSV
+    /// Reverse the last delete, cancel or edit
+    Undo {
+        /// Number of operations to undo
+        #[arg(default_value_t = 1)]
+        count: usize,
+        /// List operations that can be undone, most recent first
+        #[arg(long, conflicts_with = "count")]
+        list: bool,
+    },
+    /// Export frames as JSON, CSV or TSV (all of them unless a range is given)
+    // -f has meant --format here since the first release, so --from has no
+    // short flag on export
+    #[command(mut_arg("from", |a| a.short(None)))]
     Export {
-        /// Output format (json or csv)
-        #[arg(short, long, default_value = "json")]
-        format: commands::ExportFormat,
+        #[command(flatten)]
+        range: RangeArgs,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[command(flatten)]
+        round: RoundArgs,
+        /// Same as --format
+        #[arg(short = 'f', value_name = "FORMAT", value_enum, ignore_case = true, conflicts_with = "format")]
+        short_format: Option<Format>,
+    },
+    /// Import frames from a file exported by timer, Watson or Toggl
+    Import {
+        /// File to read
+        file: std::path::PathBuf,
+        /// File format, detected from the contents if not given. Not --format,
+        /// which is the global output format option
+        #[arg(long, value_enum, ignore_case = true)]
+        from_format: Option<import::ImportFormat>,
+        /// Show what would be imported without saving anything
+        #[arg(long)]
+        dry_run: bool,
     },
     /// Generate shell completions
     Completions {
@@ -103,6 +236,319 @@ enum Commands {
         #[arg(short, long)]
         quiet: bool,
     },
+    /// Find overlapping, negative, overlong and stray open frames
+    Check {
+        /// Report frames longer than this many hours
+        #[arg(long, default_value_t = check::DEFAULT_MAX_HOURS, value_parser = clap::value_parser!(i64).range(1..=10_000))]
+        max_hours: i64,
+        /// Offer a fix for each problem
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Manage per-project time budgets
+    Budget {
+        #[command(subcommand)]
+        command: BudgetCommand,
+    },
+    /// Manage hourly rates for projects and tags
+    Rate {
+        #[command(subcommand)]
+        command: RateCommand,
+    },
+    /// Manage which projects are billable by default
+    Billable {
+        #[command(subcommand)]
+        command: BillableCommand,
+    },
+    /// Show or change settings in the config file
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+    /// Inspect or upgrade the database schema
+    Db {
+        #[command(subcommand)]
+        command: DbCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum RateCommand {
+    /// Set or replace the hourly rate for a project (and its sub-projects)
+    /// or a tag (+tag), which overrides the project rate
+    Set {
+        /// Project name or +tag
+        target: RateTarget,
+        /// Hourly amount (e.g. 120 or 97.50)
+        amount: String,
+        /// Currency code (e.g. USD)
+        currency: String,
+    },
+    /// List rates
+    List,
+    /// Remove a rate
+    Remove {
+        /// Project name or +tag
+        target: RateTarget,
+    },
+}
+
+#[derive(Subcommand)]
+enum BillableCommand {
+    /// Set whether new frames on a project (and its sub-projects) are
+    /// billable
+    Set {
+        /// Project name
+        project: String,
+        /// yes or no
+        #[arg(value_parser = parse_yes_no, action = clap::ArgAction::Set)]
+        billable: bool,
+    },
+    /// List projects with a billable default
+    List,
+    /// Remove a project's default, making it billable again
+    Remove {
+        /// Project name
+        project: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum BudgetCommand {
+    /// Set or replace a project's budget
+    Set {
+        /// Project name
+        project: String,
+        /// Budgeted time (e.g. 40h, 7h30m)
+        #[arg(value_parser = parse_duration)]
+        limit: Duration,
+        /// How often the budget resets (day, week, month, year or total)
+        #[arg(long, default_value = "total")]
+        per: budget::BudgetPeriod,
+    },
+    /// List budgets with current usage
+    List,
+    /// Remove a project's budget
+    Remove {
+        /// Project name
+        project: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Print a setting
+    Get {
+        /// Setting name (db_path, default_range, duration_format, week_start, start_switch)
+        key: String,
+    },
+    /// Change a setting
+    Set {
+        /// Setting name
+        key: String,
+        /// New value
+        value: String,
+    },
+    /// Remove a setting, restoring its default
+    Unset {
+        /// Setting name
+        key: String,
+    },
+    /// Print all settings
+    List,
+}
+
+impl Commands {
+    /// Whether the command prints output that `--format` applies to.
+    fn has_formatted_output(&self) -> bool {
+        matches!(
+            self,
+            Commands::Status { .. }
+                | Commands::Log { .. }
+                | Commands::Report { .. }
+                | Commands::Projects { .. }
+                | Commands::Tags
+                | Commands::Export { .. }
+        )
+    }
+}
+
+/// Range and filter flags that make `delete` act on matching frames.
+const SELECTORS: [&str; 11] = [
+    "from",
+    "to",
+    "all",
+    "day",
+    "week",
+    "month",
+    "year",
+    "projects",
+    "tags",
+    "exclude_projects",
+    "exclude_tags",
+];
+
+/// Date range selection shared by `log`, `report`, `export` and `delete`.
+#[derive(Args)]
+struct RangeArgs {
+    /// Start date (YYYY-MM-DD, today, monday, last week, -3d, 2024-W12, ...)
+    #[arg(
+        short,
+        long,
+        value_parser = parse_date,
+        conflicts_with = "period",
+        allow_hyphen_values = true
+    )]
+    from: Option<DateRange>,
+    /// End date (same formats as --from)
+    #[arg(
+        short,
+        long,
+        value_parser = parse_date,
+        conflicts_with = "period",
+        allow_hyphen_values = true
+    )]
+    to: Option<DateRange>,
+    /// Show all entries (ignore date range)
+    #[arg(short, long, conflicts_with_all = ["from", "to", "period"])]
+    all: bool,
+    /// Show today
+    #[arg(long, group = "period")]
+    day: bool,
+    /// Show the current week
+    #[arg(long, group = "period")]
+    week: bool,
+    /// Show the current month
+    #[arg(long, group = "period")]
+    month: bool,
+    /// Show the current year
+    #[arg(long, group = "period")]
+    year: bool,
+}
+
+impl RangeArgs {
+    /// The selected range, or `None` for all time. `default` applies when
+    /// no range flags were given.
+    fn resolve(&self, default: DefaultRange) -> Option<DateRange> {
+        if self.all {
+            return None;
+        }
+        let period = if self.day {
+            Some(Period::Day)
+        } else if self.week {
+            Some(Period::Week)
+        } else if self.month {
+            Some(Period::Month)
+        } else if self.year {
+            Some(Period::Year)
+        } else if self.from.is_some() || self.to.is_some() {
+            None
+        } else {
+            match default {
+                DefaultRange::Day => Some(Period::Day),
+                DefaultRange::Week => Some(Period::Week),
+                DefaultRange::Month => Some(Period::Month),
+                DefaultRange::Year => Some(Period::Year),
+                DefaultRange::All => return None,
+            }
+        };
+        Some(DateRange::resolve(self.from, self.to, period))
+    }
+}
+
+/// Project and tag filters shared by `log`, `report`, `export` and `delete`.
+/// Each flag can be repeated and takes a glob pattern like `client-*`.
+#[derive(Args)]
+struct FilterArgs {
+    /// Only include these projects and their sub-projects
+    #[arg(short, long = "project", value_name = "PATTERN")]
+    projects: Vec<String>,
+    /// Only include frames with one of these tags
+    #[arg(long = "tag", value_name = "PATTERN", value_parser = parse_tag_filter)]
+    tags: Vec<String>,
+    /// Leave out these projects and their sub-projects
+    #[arg(long = "exclude-project", value_name = "PATTERN")]
+    exclude_projects: Vec<String>,
+    /// Leave out frames with any of these tags
+    #[arg(long = "exclude-tag", value_name = "PATTERN", value_parser = parse_tag_filter)]
+    exclude_tags: Vec<String>,
+}
+
+impl From<FilterArgs> for Filter {
+    fn from(args: FilterArgs) -> Self {
+        Self {
+            projects: args.projects,
+            tags: args.tags,
+            exclude_projects: args.exclude_projects,
+            exclude_tags: args.exclude_tags,
+        }
+    }
+}
+
+/// The billable flag for `start` and `edit`. Neither flag means the
+/// project's default for `start` and no change for `edit`.
+#[derive(Args)]
+struct BillArgs {
+    /// Mark the frame as billable
+    #[arg(long, conflicts_with = "no_bill")]
+    billable: bool,
+    /// Mark the frame as non-billable
+    #[arg(long)]
+    no_bill: bool,
+}
+
+impl BillArgs {
+    fn value(&self) -> Option<bool> {
+        match (self.billable, self.no_bill) {
+            (true, _) => Some(true),
+            (_, true) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Billing rounding shared by `report` and `export`. Settings from the
+/// config file apply unless overridden here.
+#[derive(Args)]
+struct RoundArgs {
+    /// Round billed time to this increment (e.g. 6m, 15m)
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    round: Option<Duration>,
+    /// Round up, to the nearest increment or down
+    #[arg(long, value_name = "MODE")]
+    round_mode: Option<RoundMode>,
+    /// Round each frame, or each project's total for a day
+    #[arg(long, value_name = "frame|day")]
+    round_per: Option<RoundPer>,
+    /// Ignore rounding settings from the config file
+    #[arg(long, conflicts_with_all = ["round", "round_mode", "round_per"])]
+    no_round: bool,
+}
+
+impl RoundArgs {
+    /// The rounding rule to apply, if any increment is set.
+    fn resolve(&self, config: &config::Config) -> Option<Rounding> {
+        if self.no_round {
+            return None;
+        }
+        Some(Rounding {
+            increment: self.round.or(config.round_to)?,
+            mode: self.round_mode.unwrap_or(config.round_mode),
+            per: self.round_per.unwrap_or(config.round_per),
+        })
+    }
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+    /// Apply pending schema migrations
+    Migrate {
+        /// Show pending migrations without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Show the current and supported schema version
+    Version,
 }
 
 fn parse_tag(s: &str) -> Result<String, String> {
@@ -111,35 +557,214 @@ fn parse_tag(s: &str) -> Result<String, String> {
         .ok_or_else(|| format!("tags must start with +: {s}"))
 }
 
-fn parse_date(s: &str) -> Result<NaiveDate, String> {
-    NaiveDate::parse_from_str(s, "%Y-%m-%d")
-        .map_err(|_| format!("invalid date format, expected YYYY-MM-DD: {s}"))
+fn parse_tag_filter(s: &str) -> Result<String, String> {
+    Ok(s.strip_prefix('+').unwrap_or(s).to_string())
+}
+
+fn parse_condition(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !value.is_empty() => Ok((key.trim().to_lowercase(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, e.g. project=acme: {s}")),
+    }
+}
+
+fn parse_yes_no(s: &str) -> Result<bool, String> {
+    match s.to_lowercase().as_str() {
+        "yes" | "true" | "on" => Ok(true),
+        "no" | "false" | "off" => Ok(false),
+        _ => Err(format!("expected yes or no, got {s}")),
+    }
+}
+
+fn parse_grid(s: &str) -> Result<Period, String> {
+    match s.to_lowercase().as_str() {
+        "day" => Ok(Period::Day),
+        "week" => Ok(Period::Week),
+        "month" => Ok(Period::Month),
+        _ => Err(format!("unknown grid: {s} (expected day, week or month)")),
+    }
+}
+
+fn parse_at(s: &str) -> Result<DateTime<Local>, String> {
+    time::parse_datetime(s).map_err(|e| e.to_string())
+}
+
+fn parse_ago(s: &str) -> Result<DateTime<Local>, String> {
+    let d = time::parse_duration(s).map_err(|e| e.to_string())?;
+    Local::now()
+        .checked_sub_signed(d)
+        .ok_or_else(|| format!("duration too large: {s}"))
+}
+
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    time::parse_duration(s).map_err(|e| e.to_string())
+}
+
+fn parse_date(s: &str) -> Result<DateRange, String> {
+    DateRange::parse(s).map_err(|e| e.to_string())
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
-    let mut conn = db::open()?;
+    if cli.format.is_some() && !cli.command.has_formatted_output() {
+        anyhow::bail!("--format only applies to status, log, report, projects, tags and export");
+    }
+
+    // Config commands work without (and even with a broken) config file
+    if let Commands::Config { command } = cli.command {
+        return match command {
+            ConfigCommand::Get { key } => commands::config_get(&key),
+            ConfigCommand::Set { key, value } => commands::config_set(&key, &value),
+            ConfigCommand::Unset { key } => commands::config_unset(&key),
+            ConfigCommand::List => commands::config_list(),
+        };
+    }
+
+    let config = config::load()?;
+    frame::set_duration_format(config.duration_format);
+    if let Some(day) = config.week_start {
+        time::set_week_start(day);
+    }
+
+    // `db` inspects the schema itself, so don't migrate behind its back
+    let db_path = config.db_path.as_deref();
+    let mut conn = match cli.command {
+        Commands::Db { .. } => db::connect(db_path)?,
+        _ => db::open(db_path)?,
+    };
+    let format = cli.format.unwrap_or(Format::Text);
 
     match cli.command {
-        Commands::Start { project, tags } => commands::start(&conn, &project, &tags),
-        Commands::Stop => commands::stop(&conn),
-        Commands::Status => commands::status(&conn),
-        Commands::Log { from, to, all } => commands::log(&conn, from, to, all),
-        Commands::Cancel => commands::cancel(&conn),
-        Commands::Delete { id } => commands::delete(&conn, id),
-        Commands::Projects => commands::projects(&conn),
-        Commands::Tags => commands::tags(&conn),
-        Commands::Report { from, to, by_tag, all } => commands::report(&conn, from, to, by_tag, all),
+        Commands::Start {
+            project,
+            tags,
+            note,
+            at,
+            ago,
+            switch,
+            no_switch,
+            bill,
+        } => {
+            let switch = switch || (config.start_switch && !no_switch);
+            let (note, at) = (note.as_deref(), at.or(ago));
+            commands::start(&mut conn, &project, &tags, note, bill.value(), at, switch)
+        }
+        Commands::Stop { note, at, ago } => commands::stop(&conn, note.as_deref(), at.or(ago)),
+        Commands::Pause => commands::pause(&conn),
+        Commands::Resume => commands::resume(&conn),
+        Commands::Status {
+            template,
+            not_tracking,
+        } => commands::status(&conn, format, template.as_deref(), &not_tracking),
+        Commands::Log {
+            range,
+            filter,
+            notes,
+        } => commands::log(&conn, range.resolve(config.default_range), filter.into(), notes, format),
+        Commands::Cancel => commands::cancel(&mut conn),
+        Commands::Delete {
+            id: Some(id), ..
+        } => commands::delete(&mut conn, id),
+        Commands::Delete {
+            range, filter, yes, ..
+        } => commands::delete_matching(&mut conn, range.resolve(DefaultRange::All), filter.into(), yes),
+        Commands::Projects { tree } => commands::projects(&conn, tree, format),
+        Commands::Tags => commands::tags(&conn, format),
+        Commands::Report {
+            range,
+            by_tag,
+            by_day,
+            grid,
+            earnings,
+            filter,
+            round,
+        } => {
+            let options = commands::ReportOptions {
+                by_tag,
+                by_day,
+                grid,
+                earnings,
+                // Timesheets show raw time, so round_to from the config doesn't apply
+                rounding: if by_day || grid.is_some() { None } else { round.resolve(&config) },
+                format,
+            };
+            commands::report(&conn, range.resolve(config.default_range), filter.into(), options)
+        }
         Commands::Edit {
             id,
+            conditions,
             project,
             tags,
             start,
             end,
-        } => commands::edit(&conn, id, project, tags, start, end),
-        Commands::Restart => commands::restart(&conn),
-        Commands::Export { format } => commands::export(&conn, format),
+            note,
+            bill,
+            force,
+            yes,
+        } => {
+            let edit = commands::FrameEdit {
+                project,
+                tags,
+                start,
+                end,
+                note,
+                billable: bill.value(),
+            };
+            match id {
+                Some(id) => commands::edit(&mut conn, id, edit, force),
+                None => commands::edit_where(&mut conn, &conditions, edit, yes),
+            }
+        }
+        Commands::Restart => commands::restart(&mut conn),
+        Commands::Undo { list: true, .. } => commands::undo_list(&conn),
+        Commands::Undo { count, .. } => commands::undo(&mut conn, count),
+        Commands::Export {
+            range,
+            filter,
+            round,
+            short_format,
+        } => {
+            let range = range.resolve(DefaultRange::All);
+            let format = cli.format.or(short_format);
+            commands::export(&conn, format, range, filter.into(), round.resolve(&config))
+        }
+        Commands::Import {
+            file,
+            from_format,
+            dry_run,
+        } => commands::import(&mut conn, &file, from_format, dry_run),
         Commands::Completions { shell } => commands::completions(shell),
         Commands::Switch { quiet } => commands::switch(&mut conn, quiet),
+        Commands::Check { max_hours, fix } => commands::check(&mut conn, max_hours, fix),
+        Commands::Config { .. } => unreachable!("handled above"),
+        Commands::Budget { command } => match command {
+            BudgetCommand::Set {
+                project,
+                limit,
+                per,
+            } => commands::budget_set(&conn, &project, limit, per),
+            BudgetCommand::List => commands::budget_list(&conn),
+            BudgetCommand::Remove { project } => commands::budget_remove(&conn, &project),
+        },
+        Commands::Rate { command } => match command {
+            RateCommand::Set {
+                target,
+                amount,
+                currency,
+            } => commands::rate_set(&conn, target, &amount, &currency),
+            RateCommand::List => commands::rate_list(&conn),
+            RateCommand::Remove { target } => commands::rate_remove(&conn, target),
+        },
+        Commands::Billable { command } => match command {
+            BillableCommand::Set { project, billable } => {
+                commands::billable_set(&conn, &project, billable)
+            }
+            BillableCommand::List => commands::billable_list(&conn),
+            BillableCommand::Remove { project } => commands::billable_remove(&conn, &project),
+        },
+        Commands::Db { command } => match command {
+            DbCommand::Migrate { dry_run } => commands::db_migrate(&mut conn, dry_run),
+            DbCommand::Version => commands::db_version(&conn),
+        },
     }
 }
@@ -0,0 +1,161 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+
+use crate::frame::Frame;
+use crate::journal;
+
+/// Frames listed before a bulk change.
+const PREVIEW_LIMIT: usize = 10;
+
+/// Output format selected with the global `--format` flag.
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+pub fn print_json<T: Serialize + ?Sized>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// List the frames a bulk change will touch, up to [`PREVIEW_LIMIT`].
+pub fn print_preview(frames: &[Frame]) {
+    for frame in frames.iter().take(PREVIEW_LIMIT) {
+        println!("  {}", journal::describe(frame));
+    }
+    if frames.len() > PREVIEW_LIMIT {
+        println!("  ... and {} more", frames.len() - PREVIEW_LIMIT);
+    }
+}
+
+/// `Started acme +dev`, noting when the frame isn't billable.
+pub fn print_started(frame: &Frame) {
+    let billable = if frame.billable { "" } else { " (non-billable)" };
+    println!("Started {}{}", frame.label(), billable);
+}
+
+/// `Stopped acme +dev (1h 5m)`.
+pub fn print_stopped(frame: &Frame) {
+    println!("Stopped {} ({})", frame.label(), Frame::format_duration(frame.duration()));
+}
+
+/// Ask a yes/no question on the terminal. Anything but `y` is a no.
+pub fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Print a header line and rows as CSV or TSV. Any other format is treated
+/// as TSV.
+pub fn print_table(format: Format, header: &[&str], rows: &[Vec<String>]) {
+    let line = |fields: Vec<String>| match format {
+        Format::Csv => fields.join(","),
+        _ => fields.join("\t"),
+    };
+    let escape = |s: &str| match format {
+        Format::Csv => escape_csv(s),
+        _ => s.replace(['\t', '\n'], " "),
+    };
+
+    println!("{}", line(header.iter().map(|h| escape(h)).collect()));
+    for row in rows {
+        println!("{}", line(row.iter().map(|f| escape(f)).collect()));
+    }
+}
+
+fn escape_csv(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Tags joined by commas for one CSV/TSV cell. Tags are free-form, so a comma
+/// or backslash inside one is escaped with a backslash.
+pub fn join_tags(tags: &[String]) -> String {
+    let escaped: Vec<String> =
+        tags.iter().map(|t| t.replace('\\', "\\\\").replace(',', "\\,")).collect();
+    escaped.join(",")
+}
+
+/// A frame as it appears in `log` and `export` output, and as `import`
+/// reads it back.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FrameRecord {
+    pub id: i64,
+    pub project: String,
+    pub start_time: String,
+    pub end_time: Option<String>,
+    pub tags: Vec<String>,
+    pub duration_seconds: i64,
+    pub paused_seconds: i64,
+    pub notes: Option<String>,
+    pub uuid: String,
+    pub created_at: String,
+    pub updated_at: String,
+    /// Always set on export; missing from exports made before the flag
+    pub billable: Option<bool>,
+}
+
+impl FrameRecord {
+    pub const HEADER: &'static [&'static str] = &[
+        "id",
+        "project",
+        "start_time",
+        "end_time",
+        "tags",
+        "duration_seconds",
+        "paused_seconds",
+        "notes",
+        "uuid",
+        "created_at",
+        "updated_at",
+        "billable",
+    ];
+
+    /// Fields in `HEADER` order, with tags joined by [`join_tags`].
+    pub fn to_row(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.project.clone(),
+            self.start_time.clone(),
+            self.end_time.clone().unwrap_or_default(),
+            join_tags(&self.tags),
+            self.duration_seconds.to_string(),
+            self.paused_seconds.to_string(),
+            self.notes.clone().unwrap_or_default(),
+            self.uuid.clone(),
+            self.created_at.clone(),
+            self.updated_at.clone(),
+            self.billable.map(|b| b.to_string()).unwrap_or_default(),
+        ]
+    }
+}
+
+impl From<&Frame> for FrameRecord {
+    fn from(frame: &Frame) -> Self {
+        Self {
+            id: frame.id,
+            project: frame.project.clone(),
+            start_time: frame.start_time.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            end_time: frame.end_time.map(|t| t.format("%Y-%m-%dT%H:%M:%S").to_string()),
+            tags: frame.tags.clone(),
+            duration_seconds: frame.duration().num_seconds(),
+            paused_seconds: frame.paused_duration().num_seconds(),
+            notes: frame.notes.clone(),
+            uuid: frame.uuid.clone(),
+            created_at: frame.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            updated_at: frame.updated_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            billable: Some(frame.billable),
+        }
+    }
+}
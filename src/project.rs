@@ -0,0 +1,32 @@
+use chrono::Duration;
+use std::collections::BTreeMap;
+
+/// Separates levels in a project path like `acme/backend`.
+pub const SEPARATOR: char = '/';
+
+/// A level in the project hierarchy with the time tracked on it and
+/// everything below it.
+#[derive(Default)]
+pub struct ProjectTree {
+    pub total: Duration,
+    pub children: BTreeMap<String, ProjectTree>,
+}
+
+impl ProjectTree {
+    /// Add time to `project` and each of its parents.
+    pub fn add(&mut self, project: &str, duration: Duration) {
+        self.total += duration;
+        let mut node = self;
+        for part in project.split(SEPARATOR) {
+            node = node.children.entry(part.to_string()).or_default();
+            node.total += duration;
+        }
+    }
+
+    /// Children with the most time first, ties by name.
+    pub fn by_total(&self) -> Vec<(&str, &ProjectTree)> {
+        let mut children: Vec<_> = self.children.iter().map(|(n, t)| (n.as_str(), t)).collect();
+        children.sort_by_key(|(_, t)| std::cmp::Reverse(t.total));
+        children
+    }
+}
@@ -0,0 +1,172 @@
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection};
+use std::fmt;
+
+use crate::frame::Frame;
+use crate::project::SEPARATOR;
+
+/// What a rate applies to: a project (and its sub-projects), or frames with
+/// a tag. Written `acme` or `+urgent` on the command line.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RateTarget {
+    Project(String),
+    Tag(String),
+}
+
+impl RateTarget {
+    fn kind(&self) -> &'static str {
+        match self {
+            RateTarget::Project(_) => "project",
+            RateTarget::Tag(_) => "tag",
+        }
+    }
+
+    fn name(&self) -> &str {
+        match self {
+            RateTarget::Project(name) | RateTarget::Tag(name) => name,
+        }
+    }
+}
+
+impl std::str::FromStr for RateTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('+') {
+            Some("") => Err("missing tag name after +".to_string()),
+            Some(tag) => Ok(RateTarget::Tag(tag.to_string())),
+            None if s.is_empty() => Err("missing project name".to_string()),
+            None => Ok(RateTarget::Project(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for RateTarget {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RateTarget::Project(name) => write!(f, "{}", name),
+            RateTarget::Tag(name) => write!(f, "+{}", name),
+        }
+    }
+}
+
+/// An hourly rate in hundredths of `currency`.
+#[derive(Clone, Debug)]
+pub struct Rate {
+    pub target: RateTarget,
+    pub cents: i64,
+    pub currency: String,
+}
+
+impl Rate {
+    /// What `seconds` of work earn at this rate, rounded to the nearest
+    /// hundredth.
+    pub fn amount(&self, seconds: i64) -> i64 {
+        let amount = (i128::from(seconds) * i128::from(self.cents) + 1800) / 3600;
+        i64::try_from(amount).unwrap_or(i64::MAX)
+    }
+}
+
+/// Every rate, for looking up many frames at once.
+pub struct Rates(Vec<Rate>);
+
+impl Rates {
+    pub fn load(conn: &Connection) -> Result<Self> {
+        all(conn).map(Rates)
+    }
+
+    /// The rate for `frame`: its first tag that has one, otherwise the rate
+    /// of its project or the nearest parent project that has one.
+    pub fn for_frame(&self, frame: &Frame) -> Option<&Rate> {
+        let tag_rate = frame.tags.iter().find_map(|tag| self.get(&RateTarget::Tag(tag.clone())));
+        tag_rate.or_else(|| {
+            let mut project = frame.project.as_str();
+            loop {
+                if let Some(rate) = self.get(&RateTarget::Project(project.to_string())) {
+                    return Some(rate);
+                }
+                project = &project[..project.rfind(SEPARATOR)?];
+            }
+        })
+    }
+
+    pub fn get(&self, target: &RateTarget) -> Option<&Rate> {
+        self.0.iter().find(|r| &r.target == target)
+    }
+}
+
+pub fn set(conn: &Connection, rate: &Rate) -> Result<()> {
+    conn.execute(
+        "INSERT INTO rates (kind, name, cents, currency) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(kind, name) DO UPDATE SET cents = excluded.cents, currency = excluded.currency",
+        params![rate.target.kind(), rate.target.name(), rate.cents, rate.currency],
+    )
+    .context("failed to save rate")?;
+    Ok(())
+}
+
+/// Remove a rate. Returns false if there was none.
+pub fn remove(conn: &Connection, target: &RateTarget) -> Result<bool> {
+    let changes = conn
+        .execute(
+            "DELETE FROM rates WHERE kind = ?1 AND name = ?2",
+            params![target.kind(), target.name()],
+        )
+        .context("failed to remove rate")?;
+    Ok(changes > 0)
+}
+
+/// Project rates, then tag rates, each by name.
+pub fn all(conn: &Connection) -> Result<Vec<Rate>> {
+    let mut stmt =
+        conn.prepare("SELECT kind, name, cents, currency FROM rates ORDER BY kind, name")?;
+    let rates = stmt
+        .query_map([], |row| {
+            let kind: String = row.get(0)?;
+            let name: String = row.get(1)?;
+            Ok(Rate {
+                target: match kind.as_str() {
+                    "tag" => RateTarget::Tag(name),
+                    _ => RateTarget::Project(name),
+                },
+                cents: row.get(2)?,
+                currency: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rates)
+}
+
+/// Largest accepted amount in hundredths, so that totals stay far from
+/// overflowing.
+const MAX_AMOUNT: i64 = 100_000_000;
+
+/// Parse a non-negative amount with up to two decimals into hundredths.
+pub fn parse_amount(s: &str) -> Result<i64> {
+    let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+    let digits = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    if !digits(whole) || (!fraction.is_empty() && !digits(fraction)) || fraction.len() > 2 {
+        bail!("invalid amount: {s} (expected e.g. 120 or 97.50)");
+    }
+    let whole: i64 = whole.parse().with_context(|| format!("amount too large: {s}"))?;
+    let fraction: i64 = format!("{fraction:0<2}").parse()?;
+    whole
+        .checked_mul(100)
+        .and_then(|w| w.checked_add(fraction))
+        .filter(|&cents| cents <= MAX_AMOUNT)
+        .with_context(|| format!("amount too large: {s} (at most {})", format_amount(MAX_AMOUNT)))
+}
+
+/// A three-letter currency code such as USD, uppercased.
+pub fn parse_currency(s: &str) -> Result<String> {
+    if s.len() != 3 || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+        bail!("invalid currency: {s} (expected a code like USD or EUR)");
+    }
+    Ok(s.to_uppercase())
+}
+
+/// Hundredths as `1234.50`.
+pub fn format_amount(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, cents.abs() / 100, cents.abs() % 100)
+}
@@ -0,0 +1,174 @@
+use chrono::Duration;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::frame::Frame;
+use crate::time::DateRange;
+
+/// Which way billed time is rounded.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundMode {
+    Up,
+    #[default]
+    Nearest,
+    Down,
+}
+
+impl std::str::FromStr for RoundMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "up" => Ok(RoundMode::Up),
+            "nearest" => Ok(RoundMode::Nearest),
+            "down" => Ok(RoundMode::Down),
+            _ => Err(format!("unknown rounding mode: {s} (expected up, nearest or down)")),
+        }
+    }
+}
+
+/// What gets rounded: each frame, or each project's total for a day.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundPer {
+    #[default]
+    Frame,
+    Day,
+}
+
+impl std::str::FromStr for RoundPer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "frame" => Ok(RoundPer::Frame),
+            "day" => Ok(RoundPer::Day),
+            _ => Err(format!("unknown rounding scope: {s} (expected frame or day)")),
+        }
+    }
+}
+
+/// A billing rounding rule. Only reported durations are rounded; frames
+/// keep their exact times.
+#[derive(Clone, Copy, Debug)]
+pub struct Rounding {
+    pub increment: Duration,
+    pub mode: RoundMode,
+    pub per: RoundPer,
+}
+
+impl Rounding {
+    pub fn round(&self, d: Duration) -> Duration {
+        let step = self.increment.num_seconds().max(1);
+        let secs = d.num_seconds();
+        let steps = match self.mode {
+            RoundMode::Up => (secs + step - 1).div_euclid(step),
+            RoundMode::Nearest => (secs + step / 2).div_euclid(step),
+            RoundMode::Down => secs.div_euclid(step),
+        };
+        Duration::seconds(steps * step)
+    }
+
+    /// e.g. "15m up per frame"
+    pub fn describe(&self) -> String {
+        let mode = match self.mode {
+            RoundMode::Up => "up",
+            RoundMode::Nearest => "to nearest",
+            RoundMode::Down => "down",
+        };
+        let per = match self.per {
+            RoundPer::Frame => "frame",
+            RoundPer::Day => "day and project",
+        };
+        let minutes = self.increment.num_minutes();
+        let increment = if self.increment == Duration::minutes(minutes) {
+            format!("{minutes}m")
+        } else {
+            format!("{}s", self.increment.num_seconds())
+        };
+        format!("{increment} {mode} per {per}")
+    }
+}
+
+/// Time worked in a group, and the time billed for it after rounding.
+#[derive(Clone, Copy, Debug)]
+pub struct Billed {
+    pub raw: Duration,
+    pub rounded: Duration,
+    /// The part of `raw` on billable frames
+    pub billable: Duration,
+}
+
+impl Billed {
+    fn new(raw: Duration, rounded: Duration, billable: bool) -> Self {
+        let billable = if billable { raw } else { Duration::zero() };
+        Self { raw, rounded, billable }
+    }
+
+    pub fn non_billable(&self) -> Duration {
+        self.raw - self.billable
+    }
+}
+
+impl Default for Billed {
+    fn default() -> Self {
+        Self {
+            raw: Duration::zero(),
+            rounded: Duration::zero(),
+            billable: Duration::zero(),
+        }
+    }
+}
+
+impl std::ops::AddAssign for Billed {
+    fn add_assign(&mut self, other: Self) {
+        self.raw += other.raw;
+        self.rounded += other.rounded;
+        self.billable += other.billable;
+    }
+}
+
+/// Raw and rounded time inside `range` for each group, where `keys` gives
+/// the groups a frame counts towards. Per-day rounding splits frames at
+/// midnight and rounds each group's daily total, billable and non-billable
+/// time separately. Without a rule the rounded time equals the raw time.
+pub fn billed<'a, K: Ord>(
+    frames: &'a [Frame],
+    range: Option<DateRange>,
+    rounding: Option<&Rounding>,
+    keys: impl Fn(&'a Frame) -> Vec<K>,
+) -> BTreeMap<K, Billed> {
+    let round = |d: Duration| rounding.map_or(d, |r| r.round(d));
+    let mut totals: BTreeMap<K, Billed> = BTreeMap::new();
+
+    if rounding.is_some_and(|r| r.per == RoundPer::Day) {
+        let mut days = BTreeMap::new();
+        for frame in frames {
+            for (date, duration) in frame.daily_durations() {
+                if range.is_none_or(|r| r.start <= date && date <= r.end) {
+                    for key in keys(frame) {
+                        *days.entry((key, date, frame.billable)).or_insert(Duration::zero()) += duration;
+                    }
+                }
+            }
+        }
+        for ((key, _, billable), duration) in days {
+            *totals.entry(key).or_default() += Billed::new(duration, round(duration), billable);
+        }
+        return totals;
+    }
+
+    let bounds = range.map(|r| r.bounds());
+    for frame in frames {
+        // Only count the part of each frame inside the range
+        let duration = match bounds {
+            Some((from, to)) => frame.duration_between(from, to),
+            None => frame.duration(),
+        };
+        for key in keys(frame) {
+            *totals.entry(key).or_default() += Billed::new(duration, round(duration), frame.billable);
+        }
+    }
+    totals
+}
@@ -0,0 +1,60 @@
+use anyhow::{bail, Result};
+use chrono::Duration;
+
+use crate::frame::Frame;
+
+/// Expand `{field}` and `{field:spec}` placeholders using `lookup`, which
+/// gets the field name and optional spec. `{{` and `}}` are literal braces.
+pub fn render(
+    template: &str,
+    mut lookup: impl FnMut(&str, Option<&str>) -> Result<String>,
+) -> Result<String> {
+    let mut out = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                out.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                out.push('}');
+            }
+            '{' => {
+                let mut field = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => field.push(c),
+                        None => bail!("unclosed '{{' in template"),
+                    }
+                }
+                let (name, spec) = match field.split_once(':') {
+                    Some((name, spec)) => (name, Some(spec)),
+                    None => (field.as_str(), None),
+                };
+                out.push_str(&lookup(name.trim(), spec)?);
+            }
+            '}' => bail!("unmatched '}}' in template (use '}}}}' for a literal brace)"),
+            c => out.push(c),
+        }
+    }
+
+    Ok(out)
+}
+
+/// Format a duration for a template. Specs: none (`1h 5m`), `hm` (`1:05`),
+/// `hms` (`1:05:30`), `m` (total minutes) and `s` (total seconds).
+pub fn format_duration(d: Duration, spec: Option<&str>) -> Result<String> {
+    let secs = d.num_seconds();
+    Ok(match spec {
+        None => Frame::format_duration(d),
+        Some("hm") => format!("{}:{:02}", secs / 3600, secs % 3600 / 60),
+        Some("hms") => format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60),
+        Some("m") => (secs / 60).to_string(),
+        Some("s") => secs.to_string(),
+        Some(other) => bail!("unknown duration format '{other}' (expected hm, hms, m or s)"),
+    })
+}
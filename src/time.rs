@@ -0,0 +1,233 @@
+use anyhow::{Context, Result};
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, Months, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Weekday,
+};
+use std::sync::OnceLock;
+
+/// Parse a point in time given on the command line.
+///
+/// Accepts "HH:MM" (today), "today HH:MM", "yesterday HH:MM" and
+/// "YYYY-MM-DD HH:MM", all in local time.
+pub fn parse_datetime(s: &str) -> Result<DateTime<Local>> {
+    let s = s.trim();
+    let today = Local::now().date_naive();
+
+    if let Ok(time) = NaiveTime::parse_from_str(s, "%H:%M") {
+        return local_datetime(&today.and_time(time));
+    }
+
+    if let Some((day, time)) = s.split_once(' ') {
+        let date = match day {
+            "today" => Some(today),
+            "yesterday" => today.pred_opt(),
+            _ => NaiveDate::parse_from_str(day, "%Y-%m-%d").ok(),
+        };
+        if let (Some(date), Ok(time)) = (date, NaiveTime::parse_from_str(time.trim(), "%H:%M")) {
+            return local_datetime(&date.and_time(time));
+        }
+    }
+
+    anyhow::bail!("expected HH:MM, yesterday HH:MM or YYYY-MM-DD HH:MM")
+}
+
+/// Parse a duration like "20m", "1h30m", "90s" or "2d".
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let mut total = Duration::zero();
+    let mut digits = String::new();
+
+    for c in s.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let n: i64 = digits
+            .parse()
+            .with_context(|| format!("invalid duration: {s}"))?;
+        let part = match c {
+            'd' => Duration::try_days(n),
+            'h' => Duration::try_hours(n),
+            'm' => Duration::try_minutes(n),
+            's' => Duration::try_seconds(n),
+            _ => anyhow::bail!("invalid duration unit '{c}' (expected d, h, m or s)"),
+        };
+        total = part
+            .and_then(|part| total.checked_add(&part))
+            .with_context(|| format!("duration too large: {s}"))?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || total.is_zero() {
+        anyhow::bail!("invalid duration: {s} (e.g. 20m, 1h30m)");
+    }
+    Ok(total)
+}
+
+fn local_datetime(dt: &NaiveDateTime) -> Result<DateTime<Local>> {
+    match Local.from_local_datetime(dt) {
+        LocalResult::Single(local_dt) => Ok(local_dt),
+        LocalResult::Ambiguous(earliest, _) => Ok(earliest),
+        LocalResult::None => anyhow::bail!("invalid time (may be during DST transition)"),
+    }
+}
+
+/// The first instant of a local calendar day. Usually midnight, but later
+/// when a DST change skips midnight.
+pub fn start_of_day(date: NaiveDate) -> DateTime<Local> {
+    (0..24)
+        .find_map(|hour| {
+            Local
+                .from_local_datetime(&date.and_hms_opt(hour, 0, 0).unwrap())
+                .earliest()
+        })
+        .expect("every day has a valid local hour")
+}
+
+static WEEK_START: OnceLock<Weekday> = OnceLock::new();
+
+/// Set the first day of the week for the rest of the process. Only the first
+/// call has any effect.
+pub fn set_week_start(day: Weekday) {
+    let _ = WEEK_START.set(day);
+}
+
+/// First day of the week, Monday unless configured otherwise.
+pub fn week_start() -> Weekday {
+    WEEK_START.get().copied().unwrap_or(Weekday::Mon)
+}
+
+/// Calendar period for the `--day/--week/--month/--year` shorthands.
+#[derive(Clone, Copy, Debug)]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+/// An inclusive span of local calendar days.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl DateRange {
+    pub fn day(date: NaiveDate) -> Self {
+        Self { start: date, end: date }
+    }
+
+    /// The period containing `date`. Weeks start on `week_start()`.
+    pub fn period(period: Period, date: NaiveDate) -> Self {
+        match period {
+            Period::Day => Self::day(date),
+            Period::Week => {
+                let back = (7 + date.weekday().num_days_from_monday()
+                    - week_start().num_days_from_monday())
+                    % 7;
+                let start = date - Duration::days(back as i64);
+                Self { start, end: start + Duration::days(6) }
+            }
+            Period::Month => {
+                let start = date.with_day(1).unwrap();
+                Self { start, end: start + Months::new(1) - Duration::days(1) }
+            }
+            Period::Year => Self {
+                start: NaiveDate::from_ymd_opt(date.year(), 1, 1).unwrap(),
+                end: NaiveDate::from_ymd_opt(date.year(), 12, 31).unwrap(),
+            },
+        }
+    }
+
+    /// Parse a date expression into the days it covers.
+    ///
+    /// Accepts `YYYY-MM-DD`, `YYYY-MM`, ISO weeks like `2024-W12`, `today`,
+    /// `yesterday`, weekday names (the most recent one, including today),
+    /// `this/last week|month|year` and offsets like `-3d` or `-2w`.
+    pub fn parse(s: &str) -> Result<Self> {
+        let today = Local::now().date_naive();
+        let s = s.trim().to_lowercase();
+
+        match s.as_str() {
+            "today" => return Ok(Self::day(today)),
+            "yesterday" => return Ok(Self::day(today - Duration::days(1))),
+            _ => {}
+        }
+
+        if let Some((which, unit)) = s.split_once(' ') {
+            let period = match unit.trim() {
+                "week" => Period::Week,
+                "month" => Period::Month,
+                "year" => Period::Year,
+                _ => anyhow::bail!("unknown period: {unit}"),
+            };
+            let this = Self::period(period, today);
+            return match which {
+                "this" => Ok(this),
+                "last" => Ok(Self::period(period, this.start - Duration::days(1))),
+                _ => anyhow::bail!("expected 'this' or 'last': {s}"),
+            };
+        }
+
+        if let Ok(weekday) = s.parse::<Weekday>() {
+            let back = (7 + today.weekday().num_days_from_monday() as i64
+                - weekday.num_days_from_monday() as i64)
+                % 7;
+            return Ok(Self::day(today - Duration::days(back)));
+        }
+
+        if let Some(offset) = s.strip_prefix('-') {
+            let (n, unit) = offset.split_at(offset.len().saturating_sub(1));
+            let n: i64 = n.parse().with_context(|| format!("invalid offset: {s}"))?;
+            let offset = match unit {
+                "d" => Duration::try_days(n),
+                "w" => Duration::try_weeks(n),
+                _ => anyhow::bail!("invalid offset unit in {s} (expected d or w)"),
+            };
+            let date = offset
+                .and_then(|offset| today.checked_sub_signed(offset))
+                .with_context(|| format!("offset too large: {s}"))?;
+            return Ok(Self::day(date));
+        }
+
+        if let Some((year, week)) = s.split_once("-w") {
+            let year: i32 = year.parse().with_context(|| format!("invalid week: {s}"))?;
+            let week: u32 = week.parse().with_context(|| format!("invalid week: {s}"))?;
+            let start = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+                .with_context(|| format!("invalid week: {s}"))?;
+            let end = start
+                .checked_add_signed(Duration::days(6))
+                .with_context(|| format!("invalid week: {s}"))?;
+            return Ok(Self { start, end });
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(&s, "%Y-%m-%d") {
+            return Ok(Self::day(date));
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(&format!("{s}-01"), "%Y-%m-%d") {
+            return Ok(Self::period(Period::Month, date));
+        }
+
+        anyhow::bail!("unrecognized date: {s}")
+    }
+
+    /// Build the range for `--from`/`--to`, or a shorthand period. Missing
+    /// bounds default to today.
+    pub fn resolve(from: Option<Self>, to: Option<Self>, period: Option<Period>) -> Self {
+        let today = Local::now().date_naive();
+        if let Some(period) = period {
+            return Self::period(period, today);
+        }
+        Self {
+            start: from.map_or(today, |r| r.start),
+            end: to.map_or(today, |r| r.end),
+        }
+    }
+
+    /// The range as a half-open span of instants, from the first day's
+    /// midnight up to (not including) the midnight after the last day.
+    pub fn bounds(&self) -> (DateTime<Local>, DateTime<Local>) {
+        (start_of_day(self.start), start_of_day(self.end + Duration::days(1)))
+    }
+}
@@ -5,30 +5,42 @@ use std::sync::atomic::{AtomicU64, Ordering};
 
 static TEST_COUNTER: AtomicU64 = AtomicU64::new(0);
 
+/// A database and config file in a temporary directory, removed with
+/// everything else the test put there.
 struct TestDb {
+    dir: PathBuf,
     path: PathBuf,
+    config: PathBuf,
 }
 
 impl TestDb {
     fn new() -> Self {
         let id = TEST_COUNTER.fetch_add(1, Ordering::SeqCst);
-        let mut path = std::env::temp_dir();
-        path.push(format!("timer-cli-test-{}-{}.db", std::process::id(), id));
+        let dir = std::env::temp_dir().join(format!("timer-cli-test-{}-{}", std::process::id(), id));
         // Ensure clean slate
-        let _ = fs::remove_file(&path);
-        Self { path }
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("frames.db");
+        let config = dir.join("config.toml");
+        Self { dir, path, config }
+    }
+
+    /// A path for a scratch file next to the database.
+    fn file(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
     }
 
     fn cli(&self) -> Command {
         let mut cmd = Command::new(env!("CARGO_BIN_EXE_timer-cli"));
         cmd.env("TIMER_CLI_DB", &self.path);
+        cmd.env("TIMER_CLI_CONFIG", &self.config);
         cmd
     }
 }
 
 impl Drop for TestDb {
     fn drop(&mut self) {
-        let _ = fs::remove_file(&self.path);
+        let _ = fs::remove_dir_all(&self.dir);
     }
 }
 
@@ -332,3 +344,1380 @@ fn test_timer_alias() {
     let stdout = String::from_utf8_lossy(&output.stdout);
     assert!(stdout.contains("Not tracking"));
 }
+
+#[test]
+fn test_db_migrate_dry_run() {
+    let db = TestDb::new();
+
+    // Fresh database: dry run lists steps without applying them
+    let output = db.cli()
+        .args(["db", "migrate", "--dry-run"])
+        .output()
+        .expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Would apply 1: create frames table"));
+    assert!(stdout.contains("Would apply 2: move tags into frame_tags table"));
+
+    let output = db.cli().args(["db", "version"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Schema version 0"));
+
+    // Apply
+    let output = db.cli().args(["db", "migrate"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Applied 1: create frames table"));
+
+    let output = db.cli()
+        .args(["db", "migrate", "--dry-run"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Database is up to date"));
+}
+
+#[test]
+fn test_newer_schema_refused() {
+    let db = TestDb::new();
+
+    // Initialize, then pretend a future version wrote the file
+    let _ = db.cli().arg("status").output();
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    conn.pragma_update(None, "user_version", 9999).unwrap();
+    drop(conn);
+
+    let output = db.cli().arg("status").output().expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("newer than this timer-cli supports"));
+}
+
+#[test]
+fn test_legacy_tags_migrated() {
+    let db = TestDb::new();
+
+    // A pre-migration database with comma-joined tags
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE frames (
+            id INTEGER PRIMARY KEY,
+            project TEXT NOT NULL,
+            start_time INTEGER NOT NULL,
+            end_time INTEGER,
+            tags TEXT
+        );
+        INSERT INTO frames (project, start_time, end_time, tags)
+        VALUES ('legacy', strftime('%s','now') - 60, strftime('%s','now'), 'first,second');",
+    )
+    .unwrap();
+    drop(conn);
+
+    let output = db.cli().args(["log", "--all"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("legacy  +first +second"));
+}
+
+#[test]
+fn test_tag_with_comma() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "proj", "+a,b"]).output();
+    let _ = db.cli().arg("stop").output();
+
+    let output = db.cli().arg("tags").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.trim(), "+a,b");
+}
+
+#[test]
+fn test_log_and_report_tag_filter() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "wanted", "+keep"]).output();
+    let _ = db.cli().arg("stop").output();
+    let _ = db.cli().args(["start", "unwanted", "+other"]).output();
+    let _ = db.cli().arg("stop").output();
+
+    let output = db.cli().args(["log", "--tag", "+keep"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("wanted"));
+    assert!(!stdout.contains("unwanted"));
+
+    let output = db.cli().args(["report", "--tag", "keep"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("wanted"));
+    assert!(!stdout.contains("unwanted"));
+}
+
+#[test]
+fn test_project_and_tag_globs() {
+    let db = TestDb::new();
+
+    for (project, tag, at) in [
+        ("client-a", "+meeting", "2024-01-01 09:00"),
+        ("client-b", "+dev", "2024-01-01 10:00"),
+        ("internal", "+dev", "2024-01-01 11:00"),
+    ] {
+        let _ = db.cli().args(["start", project, tag, "--at", at]).output();
+        let _ = db.cli().args(["stop", "--at", &at.replace(":00", ":30")]).output();
+    }
+
+    let output = db.cli()
+        .args(["log", "--all", "--project", "client-*", "--exclude-tag", "meeting"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("client-b"));
+    assert!(!stdout.contains("client-a"));
+    assert!(!stdout.contains("internal"));
+
+    let output = db.cli()
+        .args(["report", "--from", "2024-01-01", "--tag", "dev", "--exclude-project", "client-?"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("internal"));
+    assert!(!stdout.contains("client"));
+
+    // Repeated flags match any of the patterns
+    let output = db.cli()
+        .args(["export", "--format", "csv", "-p", "internal", "-p", "client-a"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 3);
+    assert!(!stdout.contains("client-b"));
+
+    // Export with a range only includes frames in it
+    let output = db.cli()
+        .args(["export", "--format", "csv", "--from", "2024-01-02"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout.lines().count(), 1);
+
+    // -f is --from elsewhere, but export has always taken it for its format
+    let output = db.cli().args(["log", "-f", "2024-01-01", "-t", "2024-01-01"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("internal"));
+    let output = db.cli().args(["export", "-f", "csv"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("id,project,"));
+}
+
+#[test]
+fn test_report_clips_frames_to_days() {
+    let db = TestDb::new();
+    let cli = || {
+        let mut cmd = db.cli();
+        cmd.env("TZ", "Europe/Berlin");
+        cmd
+    };
+    let report = |args: &[&str]| {
+        let output = cli().arg("report").args(args).output().expect("failed to run");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let day = |date: &str| report(&["--from", date, "--to", date]);
+
+    // Across midnight: 2h on each day
+    let _ = cli().args(["start", "late", "--at", "2024-01-01 22:00"]).output();
+    let _ = cli().args(["stop", "--at", "2024-01-02 02:00"]).output();
+    assert!(day("2024-01-01").contains("late 2h 0m"));
+    assert!(day("2024-01-02").contains("late 2h 0m"));
+    assert!(report(&["--from", "2024-01-01", "--to", "2024-01-02"]).contains("late 4h 0m"));
+
+    // Spring forward: 02:00-03:00 doesn't exist on 2024-03-31
+    let _ = cli().args(["start", "spring", "--at", "2024-03-30 23:00"]).output();
+    let _ = cli().args(["stop", "--at", "2024-03-31 04:00"]).output();
+    assert!(day("2024-03-30").contains("spring 1h 0m"));
+    assert!(day("2024-03-31").contains("spring 3h 0m"));
+
+    // Fall back: 02:00-03:00 happens twice on 2024-10-27
+    let _ = cli().args(["start", "autumn", "--at", "2024-10-26 22:00"]).output();
+    let _ = cli().args(["stop", "--at", "2024-10-27 03:00"]).output();
+    assert!(day("2024-10-26").contains("autumn 2h 0m"));
+    assert!(day("2024-10-27").contains("autumn 4h 0m"));
+
+    let stdout = report(&["--from", "2024-03-30", "--to", "2024-03-31", "--by-day"]);
+    assert!(stdout.contains("2024-03-30 Sat 1h 0m"));
+    assert!(stdout.contains("2024-03-31 Sun 3h 0m"));
+    assert!(stdout.contains("Total: 4h 0m"));
+}
+
+#[test]
+fn test_report_grid() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "acme", "--at", "2024-01-01 22:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-02 02:00"]).output();
+    let _ = db.cli().args(["start", "beta", "--at", "2024-01-03 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-03 10:30"]).output();
+
+    let output = db.cli()
+        .args(["report", "--from", "2024-01-01", "--to", "2024-01-03", "--grid", "day"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert!(lines[0].contains("Mon 01-01") && lines[0].contains("Wed 01-03"));
+    assert!(lines[1].starts_with("acme") && lines[1].ends_with("4h 0m"));
+    assert!(lines[3].starts_with("Total") && lines[3].ends_with("5h 30m"));
+
+    let output = db.cli()
+        .args(["report", "--from", "2024-01-01", "--to", "2024-01-03", "--grid", "day", "--format", "csv"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("project,2024-01-01,2024-01-02,2024-01-03,total"));
+    assert!(stdout.contains("acme,7200,7200,0,14400"));
+    assert!(stdout.contains("Total,7200,7200,5400,19800"));
+
+    let output = db.cli()
+        .args(["report", "--all", "--grid", "week", "--format", "json"])
+        .output()
+        .expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json["columns"], serde_json::json!(["2024-W01"]));
+    assert_eq!(json["rows"][1]["project"], "beta");
+    assert_eq!(json["total_seconds"], 19800);
+}
+
+#[test]
+fn test_hierarchical_projects() {
+    let db = TestDb::new();
+
+    for (project, start, end) in [
+        ("acme/backend", "09:00", "11:00"),
+        ("acme/frontend", "11:00", "12:00"),
+        ("acmeco", "13:00", "14:00"),
+    ] {
+        let _ = db.cli().args(["start", project, "--at", &format!("2024-01-01 {start}")]).output();
+        let _ = db.cli().args(["stop", "--at", &format!("2024-01-01 {end}")]).output();
+    }
+
+    let output = db.cli().args(["report", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  acme 3h 0m\n    backend 2h 0m\n    frontend 1h 0m\n  acmeco 1h 0m"));
+
+    // A parent includes its sub-projects but not projects sharing a prefix
+    let output = db.cli()
+        .args(["report", "--all", "--project", "acme"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Total: 3h 0m"));
+    assert!(!stdout.contains("acmeco"));
+
+    let output = db.cli().args(["projects", "--tree"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(stdout, "acme\n  backend\n  frontend\nacmeco\n");
+}
+
+#[test]
+fn test_format_option() {
+    let db = TestDb::new();
+    let json = |args: &[&str]| -> serde_json::Value {
+        let output = db.cli().args(args).args(["--format", "json"]).output().expect("failed to run");
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).expect("invalid json")
+    };
+
+    assert_eq!(json(&["status"]), serde_json::json!({ "tracking": false }));
+
+    let _ = db.cli().args(["start", "acme", "+dev", "--at", "2024-01-01 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-01 10:00"]).output();
+    let _ = db.cli().args(["start", "beta", "--ago", "5m"]).output();
+
+    let status = json(&["status"]);
+    assert_eq!(status["tracking"], true);
+    assert_eq!(status["project"], "beta");
+    assert_eq!(status["paused"], false);
+    assert!(status["budget"].is_null());
+
+    let log = json(&["log", "--all"]);
+    assert_eq!(log[1]["project"], "acme");
+    assert_eq!(log[1]["tags"], serde_json::json!(["dev"]));
+    assert_eq!(log[1]["duration_seconds"], 3600);
+
+    let report = json(&["report", "--from", "2024-01-01", "--to", "2024-01-01"]);
+    assert_eq!(report["projects"], serde_json::json!([{ "project": "acme", "seconds": 3600, "billable_seconds": 3600, "non_billable_seconds": 0 }]));
+    assert_eq!(report["total_seconds"], 3600);
+
+    assert_eq!(json(&["projects"]), serde_json::json!(["acme", "beta"]));
+    assert_eq!(json(&["tags"]), serde_json::json!(["dev"]));
+
+    let output = db.cli()
+        .args(["report", "--from", "2024-01-01", "--to", "2024-01-01", "--format", "tsv"])
+        .output()
+        .expect("failed to run");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "project\tseconds\tbillable_seconds\tnon_billable_seconds\nacme\t3600\t3600\t0\n");
+
+    let output = db.cli().args(["export", "--format", "text"]).output().expect("failed to run");
+    assert!(!output.status.success());
+
+    // Unknown formats and commands without formatted output are rejected
+    let output = db.cli().args(["log", "--format", "xml"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["--format", "json", "start", "gamma"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--format only applies to"));
+    assert_eq!(json(&["status"])["project"], "beta");
+}
+
+#[test]
+fn test_status_template() {
+    let db = TestDb::new();
+
+    let output = db.cli()
+        .args(["status", "--not-tracking", "idle"])
+        .output()
+        .expect("failed to run");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "idle\n");
+
+    let _ = db.cli().args(["start", "acme", "+dev", "+api", "--ago", "65m"]).output();
+    let output = db.cli()
+        .args(["status", "--template", "{project} {elapsed:hm} {tags} #{id} {today:m}m {{ok}}"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("acme 1:05 +dev +api #1 "), "{stdout}");
+    assert!(stdout.trim_end().ends_with("m {ok}"));
+
+    let output = db.cli()
+        .args(["status", "--template", "{bogus}"])
+        .output()
+        .expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("unknown template field 'bogus'"));
+}
+
+#[test]
+fn test_config() {
+    let db = TestDb::new();
+
+    let output = db.cli().args(["config", "set", "duration_format", "hm"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let _ = db.cli().args(["config", "set", "default_range", "all"]).output();
+    let _ = db.cli().args(["config", "set", "start_switch", "true"]).output();
+
+    let output = db.cli().args(["config", "get", "start_switch"]).output().expect("failed to run");
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "true\n");
+
+    let output = db.cli().args(["config", "set", "week_start", "someday"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["config", "set", "colour", "red"]).output().expect("failed to run");
+    assert!(!output.status.success());
+
+    // start_switch makes start stop the running frame; --no-switch overrides it
+    let _ = db.cli().args(["start", "old", "--at", "2024-01-01 09:00"]).output();
+    let output = db.cli().args(["start", "new", "--at", "2024-01-01 10:30"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Stopped old (1:30)"));
+    let output = db.cli().args(["start", "other", "--no-switch"]).output().expect("failed to run");
+    assert!(!output.status.success());
+
+    // default_range = all shows 2024 frames without --all
+    let output = db.cli().arg("log").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("old") && stdout.contains("1:30"));
+
+    // A db_path setting is ignored while TIMER_CLI_DB is set
+    let _ = db.cli().args(["config", "set", "db_path", "/nonexistent/dir/frames.db"]).output();
+    let output = db.cli().arg("log").output().expect("failed to run");
+    assert!(output.status.success());
+
+    let output = db.cli().args(["config", "unset", "db_path"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().args(["config", "list"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("default_range = \"all\""));
+    assert!(!stdout.contains("db_path"));
+
+    // Unknown keys edited into the file are named along with the valid ones
+    fs::write(&db.config, "colour = \"red\"\n").unwrap();
+    let output = db.cli().arg("log").output().expect("failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown setting 'colour' (expected one of: db_path"));
+
+    // ...they don't stop other settings from being changed, and can be removed
+    let output = db.cli().args(["config", "set", "start_switch", "false"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().args(["config", "unset", "colour"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().arg("log").output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().args(["config", "unset", "colour"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown setting 'colour'"));
+}
+
+#[test]
+fn test_import() {
+    let db = TestDb::new();
+    let _ = db.cli().args(["start", "acme", "+dev", "--at", "2024-03-01 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 10:30"]).output();
+    let output = db.cli().arg("export").output().expect("failed to run");
+    let export = db.file("export.json");
+    fs::write(&export, &output.stdout).unwrap();
+
+    // Re-importing into the same database only finds duplicates
+    let output = db.cli().arg("import").arg(&export).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Imported 0 frame(s), skipped 1 duplicate(s)"));
+
+    let other = TestDb::new();
+    let output = other.cli().arg("import").arg(&export).output().expect("failed to run");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Imported 1 frame(s)"));
+    let output = other.cli().args(["log", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme") && stdout.contains("+dev") && stdout.contains("1h 30m"));
+
+    // Watson frames file, detected from its contents; --dry-run saves nothing
+    let watson = db.file("frames");
+    fs::write(&watson, r#"[[1709280000, 1709283600, "watson-project", "abc", ["x"], 1709283600]]"#).unwrap();
+    let output = other.cli().arg("import").arg(&watson).arg("--dry-run").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Would import 1 frame(s)"));
+    let output = other.cli().args(["log", "--all"]).output().expect("failed to run");
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("watson-project"));
+    let output = other.cli().args(["import", "--from-format", "watson"]).arg(&watson).output().expect("failed to run");
+    assert!(output.status.success());
+
+    // Toggl detailed report CSV
+    let toggl = db.file("toggl.csv");
+    fs::write(&toggl, "User,Project,Description,Start date,Start time,End date,End time,Tags\n\
+        me,,Fix bug,2024-03-02,09:00:00,2024-03-02,10:00:00,\"a, b\"\n").unwrap();
+    let output = other.cli().arg("import").arg(&toggl).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = other.cli().args(["log", "--all", "--notes"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("watson-project"));
+    assert!(stdout.contains("(no project)") && stdout.contains("+a +b") && stdout.contains("Fix bug"));
+
+    // Tags with commas survive a CSV round trip
+    let tagged = TestDb::new();
+    let _ = tagged.cli().args(["start", "x", "+a,b", "+c\\d", "--at", "2024-03-03 09:00"]).output();
+    let _ = tagged.cli().args(["stop", "--at", "2024-03-03 10:00"]).output();
+    let output = tagged.cli().args(["export", "--format", "csv"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains(r#""a\,b,c\\d""#));
+    let csv = db.file("tagged.csv");
+    fs::write(&csv, &output.stdout).unwrap();
+    let copy = TestDb::new();
+    let output = copy.cli().arg("import").arg(&csv).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = copy.cli().arg("export").output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json[0]["tags"], serde_json::json!(["a,b", "c\\d"]));
+
+    // Malformed input is rejected without importing anything
+    fs::write(&toggl, "Project,Start date,Start time,End date,End time\nweb,2024-03-02,nine,2024-03-02,10:00:00\n").unwrap();
+    let output = other.cli().arg("import").arg(&toggl).output().expect("failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("row 2"));
+}
+
+#[test]
+fn test_frame_uuids() {
+    let db = TestDb::new();
+    let _ = db.cli().args(["start", "acme", "--at", "2024-03-01 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 10:00"]).output();
+
+    // Frames written by other programs (e.g. TimerBar) get a UUID too
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    conn.execute(
+        "INSERT INTO frames (project, start_time, end_time) VALUES ('bar', 1709290800, 1709294400)",
+        [],
+    )
+    .unwrap();
+    drop(conn);
+
+    let output = db.cli().arg("export").output().expect("failed to run");
+    let mut frames: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    let uuids: Vec<&str> = frames.iter().map(|f| f["uuid"].as_str().unwrap()).collect();
+    assert_eq!(uuids.len(), 2);
+    assert!(uuids.iter().all(|u| u.len() == 36 && u.as_bytes()[14] == b'4'));
+    assert_ne!(uuids[0], uuids[1]);
+    assert!(frames[0]["created_at"].is_string() && frames[0]["updated_at"].is_string());
+
+    // The UUID survives a round trip, so re-importing finds the same frames
+    let export = db.file("export.json");
+    fs::write(&export, &output.stdout).unwrap();
+    let other = TestDb::new();
+    let _ = other.cli().arg("import").arg(&export).output();
+    let output = other.cli().arg("export").output().expect("failed to run");
+    let imported: Vec<serde_json::Value> = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(imported[0]["uuid"], frames[0]["uuid"]);
+    assert_eq!(imported[0]["updated_at"], frames[0]["updated_at"]);
+
+    // A newer version of a frame replaces the local one, even with new times
+    frames[0]["project"] = "acme/renamed".into();
+    frames[0]["start_time"] = "2024-03-01T08:30:00".into();
+    frames[0]["updated_at"] = "2099-01-01T00:00:00".into();
+    fs::write(&export, serde_json::to_string(&frames).unwrap()).unwrap();
+    let output = db.cli().arg("import").arg(&export).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Imported 0 frame(s), updated 1 older frame(s), skipped 1 duplicate(s)"));
+    let output = db.cli().args(["log", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme/renamed") && stdout.contains("1h 30m"));
+
+    // Importing it again changes nothing
+    let output = db.cli().arg("import").arg(&export).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Imported 0 frame(s), skipped 2 duplicate(s)"));
+
+    // Undo puts back the replaced frame
+    let output = db.cli().arg("undo").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Undid import over 1 frames"));
+    let output = db.cli().args(["log", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("acme/renamed") && stdout.contains("1h 0m"));
+}
+
+#[test]
+fn test_undo() {
+    let db = TestDb::new();
+    let output = db.cli().arg("undo").output().expect("failed to run");
+    assert!(!output.status.success());
+
+    let _ = db.cli().args(["start", "acme", "+dev", "--at", "2024-03-01 09:00", "--note", "first"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 10:30"]).output();
+    let _ = db.cli().args(["edit", "1", "--project", "oops", "--tags", "+wrong"]).output();
+    let _ = db.cli().args(["start", "web"]).output();
+    let _ = db.cli().arg("cancel").output();
+    let output = db.cli().args(["delete", "1"]).output().expect("failed to run");
+    assert!(output.status.success());
+
+    let output = db.cli().args(["undo", "--list"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("delete frame 1 (oops +wrong, 2024-03-01 09:00 - 10:30)"));
+    assert!(lines[1].contains("cancel frame 2 (web"));
+    assert!(lines[2].contains("edit frame 1 (acme +dev"));
+
+    // Undoing the delete brings back the frame with its id, tags and note
+    let output = db.cli().arg("undo").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Undid delete frame 1"));
+    let output = db.cli().args(["log", "--all", "--notes"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[1] oops") && stdout.contains("+wrong") && stdout.contains("first"));
+
+    // The cancelled frame can't come back while something else is running
+    let _ = db.cli().args(["start", "other"]).output();
+    let output = db.cli().args(["undo", "2"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("stop first"));
+    let _ = db.cli().arg("stop").output();
+
+    // Undo the cancel and the edit together
+    let output = db.cli().args(["undo", "2"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().arg("status").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("web"));
+    let output = db.cli().args(["log", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("[1] acme") && stdout.contains("+dev") && !stdout.contains("oops"));
+
+    let output = db.cli().args(["undo", "--list"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Nothing to undo"));
+}
+
+#[test]
+fn test_undo_keeps_later_changes() {
+    let db = TestDb::new();
+    let _ = db.cli().args(["start", "acme", "--at", "2024-03-01 09:00"]).output();
+    let _ = db.cli().args(["edit", "1", "--project", "beta"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 10:00"]).output();
+
+    // Only the project goes back; the frame stays stopped
+    let output = db.cli().arg("undo").output().expect("failed to run");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("frame 1 was stopped since, so it stays stopped"));
+    let output = db.cli().arg("status").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Not tracking"));
+    let output = db.cli().args(["log", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme") && !stdout.contains("beta") && stdout.contains("1h 0m"));
+
+    // Undoing an edit that ended a frame can't reopen it while another runs
+    let _ = db.cli().args(["start", "web", "--at", "2024-03-02 09:00"]).output();
+    let _ = db.cli().args(["edit", "2", "--end", "2024-03-02 10:00"]).output();
+    let _ = db.cli().args(["start", "other"]).output();
+    let output = db.cli().arg("undo").output().expect("failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("another frame is running - stop it first"));
+    let output = db.cli().args(["log", "--all"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("web"));
+}
+
+#[test]
+fn test_rates_and_earnings() {
+    let db = TestDb::new();
+    for (project, from, to) in [
+        ("acme/backend", "2024-03-01 09:00", "2024-03-01 19:00"),
+        ("acme/backend +urgent", "2024-03-02 09:00", "2024-03-02 10:00"),
+        ("acme/web", "2024-03-03 09:00", "2024-03-03 09:20"),
+        ("blog", "2024-03-04 09:00", "2024-03-04 11:00"),
+    ] {
+        let mut args: Vec<&str> = vec!["start"];
+        args.extend(project.split(' '));
+        let _ = db.cli().args(&args).args(["--at", from]).output();
+        let _ = db.cli().args(["stop", "--at", to]).output();
+    }
+
+    let output = db.cli().args(["rate", "set", "acme", "120", "usd"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Rate for acme: 120.00 USD/h"));
+    let _ = db.cli().args(["rate", "set", "+urgent", "180.50", "USD"]).output();
+    let output = db.cli().args(["rate", "set", "blog", "1.234", "USD"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["rate", "set", "blog", "92233720368547758", "USD"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("amount too large"));
+    let output = db.cli().args(["rate", "list"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme") && stdout.contains("+urgent  180.50 USD/h"));
+
+    // Sub-projects use the parent's rate and tag rates override it
+    let output = db.cli().args(["report", "--all", "--earnings"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme/backend +urgent"));
+    assert!(stdout.lines().any(|l| l.starts_with("acme ") && l.ends_with("1420.50 USD")));
+    assert!(stdout.contains("no rate"));
+    assert!(stdout.lines().any(|l| l.starts_with("Total") && l.ends_with("1420.50 USD")));
+
+    let output = db.cli().args(["--format", "json", "report", "--all", "--earnings"]).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    let acme = &json["clients"][0];
+    assert_eq!(acme["client"], "acme");
+    assert_eq!(acme["lines"][1]["tag"], "urgent");
+    assert_eq!(acme["lines"][2]["amount"], "40.00");
+    assert_eq!(json["clients"][1]["lines"][0]["amount"], serde_json::Value::Null);
+    assert_eq!(json["amounts"], serde_json::json!([{"currency": "USD", "amount": "1420.50"}]));
+
+    // Only time inside the range is billed
+    let output = db.cli().args(["--format", "csv", "report", "--earnings", "--from", "2024-03-03", "--to", "2024-03-03"]).output().expect("failed to run");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "client,project,tag,seconds,rate,currency,amount,billable\nacme,acme/web,,1200,120.00,USD,40.00,true\n"
+    );
+
+    let output = db.cli().args(["rate", "remove", "+urgent"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().args(["rate", "remove", "+urgent"]).output().expect("failed to run");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_rounding() {
+    let db = TestDb::new();
+    for (from, to) in [
+        ("2024-03-01 09:00", "2024-03-01 10:02"),
+        ("2024-03-01 11:00", "2024-03-01 11:05"),
+    ] {
+        let _ = db.cli().args(["start", "acme", "--at", from]).output();
+        let _ = db.cli().args(["stop", "--at", to]).output();
+    }
+    let range = ["--from", "2024-03-01", "--to", "2024-03-01"];
+
+    let output = db.cli().arg("report").args(range).args(["--round", "15m", "--round-mode", "up"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("By project (rounded 15m up per frame):"));
+    assert!(stdout.contains("acme 1h 7m (rounded: 1h 30m)"));
+
+    let output = db.cli().arg("report").args(range).args(["--round", "15m", "--round-mode", "up", "--round-per", "day"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("acme 1h 7m (rounded: 1h 15m)"));
+
+    // Rounded time is billed, raw time is kept alongside it
+    let _ = db.cli().args(["rate", "set", "acme", "100", "USD"]).output();
+    let output = db.cli().args(["--format", "csv", "report", "--earnings", "--round", "15m", "--round-mode", "up"]).args(range).output().expect("failed to run");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "client,project,tag,seconds,rounded_seconds,rate,currency,amount,billable\nacme,acme,,4020,5400,100.00,USD,150.00,true\n"
+    );
+
+    // Config applies unless --no-round is given
+    let _ = db.cli().args(["config", "set", "round_to", "6m"]).output();
+    let output = db.cli().args(["--format", "json", "report"]).args(range).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json["projects"][0]["seconds"], 4020);
+    assert_eq!(json["projects"][0]["rounded_seconds"], 3960);
+    assert_eq!(json["total_rounded_seconds"], 3960);
+    let output = db.cli().args(["--format", "json", "report", "--no-round"]).args(range).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert!(json.get("total_rounded_seconds").is_none());
+
+    let output = db.cli().args(["export"]).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json[0]["duration_seconds"], 3720);
+    assert_eq!(json[0]["rounded_seconds"], 3600);
+    assert_eq!(json[1]["rounded_seconds"], 360);
+
+    // Export can't split per-day rounding back into frames
+    let output = db.cli().args(["export", "--round-per", "day"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("export rounds each frame"));
+    let _ = db.cli().args(["config", "set", "round_per", "day"]).output();
+    let output = db.cli().args(["export"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["export", "--round-per", "frame"]).output().expect("failed to run");
+    assert!(output.status.success());
+
+    // Timesheets show raw time: the flags are rejected and the config skipped
+    let output = db.cli().args(["report", "--by-day", "--round", "15m"]).args(range).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["report", "--grid", "day", "--round-mode", "up"]).args(range).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["report", "--by-day"]).args(range).output().expect("failed to run");
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("rounded"));
+
+    // Stored times are untouched
+    let output = db.cli().args(["--format", "json", "log", "--all"]).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json[1]["end_time"], "2024-03-01T10:02:00");
+}
+
+#[test]
+fn test_billable() {
+    let db = TestDb::new();
+    let output = db.cli().args(["billable", "set", "internal", "no"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("New frames on internal are non-billable"));
+
+    // Sub-projects get the default, flags override it
+    let output = db.cli().args(["start", "internal/meetings", "--at", "2024-03-01 09:00"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Started internal/meetings (non-billable)"));
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 10:00"]).output();
+    let _ = db.cli().args(["start", "acme", "--no-bill", "--at", "2024-03-01 10:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 10:30"]).output();
+    let _ = db.cli().args(["start", "acme", "--at", "2024-03-01 11:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 13:00"]).output();
+
+    let range = ["--from", "2024-03-01", "--to", "2024-03-01"];
+    let output = db.cli().arg("report").args(range).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme 2h 30m (billable: 2h 0m, non-billable: 30m 0s)"));
+    assert!(stdout.contains("Total: 3h 30m (billable: 2h 0m, non-billable: 1h 30m)"));
+
+    let output = db.cli().args(["--format", "csv", "report"]).args(range).output().expect("failed to run");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "project,seconds,billable_seconds,non_billable_seconds\nacme,9000,7200,1800\ninternal/meetings,3600,0,3600\n"
+    );
+
+    // Non-billable time earns nothing
+    let _ = db.cli().args(["rate", "set", "acme", "100", "USD"]).output();
+    let output = db.cli().args(["--format", "json", "report", "--earnings"]).args(range).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json["clients"][0]["lines"][1]["billable"], false);
+    assert_eq!(json["clients"][0]["lines"][1]["amount"], serde_json::Value::Null);
+    assert_eq!(json["amounts"], serde_json::json!([{"currency": "USD", "amount": "200.00"}]));
+
+    let output = db.cli().args(["edit", "2", "--billable"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().args(["export"]).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json[0]["billable"], false);
+    assert_eq!(json[1]["billable"], true);
+
+    let _ = db.cli().args(["undo"]).output();
+    let output = db.cli().args(["export"]).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json[1]["billable"], false);
+
+    // Restarting a non-billable frame says so too
+    let _ = db.cli().args(["start", "internal/meetings", "+sync", "--at", "2024-03-01 14:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-03-01 15:00"]).output();
+    let output = db.cli().arg("restart").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Started internal/meetings +sync (non-billable)"));
+}
+
+#[test]
+fn test_bulk_edit_and_delete() {
+    let db = TestDb::new();
+    for (project, from, to) in [
+        ("scratch", "2024-01-01 09:00", "2024-01-01 10:00"),
+        ("acme-old", "2024-01-02 09:00", "2024-01-02 10:00"),
+        ("acme-old/web", "2024-01-02 11:00", "2024-01-02 12:00"),
+        ("scratch", "2024-01-05 09:00", "2024-01-05 10:00"),
+        ("acme-old", "2024-01-06 09:00", "2024-01-06 10:00"),
+    ] {
+        let _ = db.cli().args(["start", project, "--at", from]).output();
+        let _ = db.cli().args(["stop", "--at", to]).output();
+    }
+    let projects = || {
+        let output = db.cli().args(["--format", "json", "log", "--all"]).output().expect("failed to run");
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+        json.as_array().unwrap().iter().map(|f| f["project"].as_str().unwrap().to_string()).collect::<Vec<_>>()
+    };
+
+    // Without --yes and no answer nothing changes
+    let output = db.cli().args(["edit", "--where", "project=acme-old", "--set-project", "acme"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("frame 2 (acme-old, 2024-01-02 09:00 - 10:00)"));
+    assert!(stdout.contains("Change project to acme on these 3 frame(s)? [y/N] Nothing changed"));
+
+    // A project condition includes sub-projects, like --project does
+    let output = db.cli().args(["edit", "--where", "project=acme-old", "--set-project", "acme", "--yes"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated 3 frame(s)"));
+    assert_eq!(projects(), ["acme", "scratch", "acme", "acme", "scratch"]);
+
+    let output = db.cli().args(["edit", "--where", "colour=red", "-p", "x", "--yes"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["edit", "--where", "project=acme", "--start", "09:00"]).output().expect("failed to run");
+    assert!(!output.status.success());
+
+    let output = db.cli().args(["delete", "--from", "2024-01-01", "--to", "2024-01-02", "--project", "scratch", "-y"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Deleted 1 frame(s)"));
+    assert_eq!(projects(), ["acme", "scratch", "acme", "acme"]);
+    let output = db.cli().arg("delete").output().expect("failed to run");
+    assert!(!output.status.success());
+
+    // Each bulk change undoes as one
+    let _ = db.cli().arg("undo").output();
+    let output = db.cli().arg("undo").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Undid edit 3 frames (project to acme)"));
+    assert_eq!(projects(), ["acme-old", "scratch", "acme-old/web", "acme-old", "scratch"]);
+
+    // Only frames starting in the range go, not one running past midnight into it
+    let _ = db.cli().args(["start", "late", "--at", "2024-01-07 23:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-08 00:10"]).output();
+    let output = db.cli().args(["delete", "--from", "2024-01-08", "--to", "2024-01-08", "-y"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No frames found"));
+    let output = db.cli().args(["edit", "--where", "from=2024-01-08", "--note", "x", "-y"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("No frames found"));
+
+    // The running frame is skipped
+    let _ = db.cli().args(["start", "late"]).output();
+    let output = db.cli().args(["delete", "--project", "late", "-y"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Skipping the running frame") && stdout.contains("Deleted 1 frame(s)"));
+    let output = db.cli().arg("status").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("late"));
+}
+
+#[test]
+fn test_notes() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "proj", "-m", "planning"]).output();
+    let output = db.cli()
+        .args(["stop", "-m", "fixed login bug"])
+        .output()
+        .expect("failed to run");
+    assert!(output.status.success());
+
+    // Hidden by default
+    let output = db.cli().arg("log").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("fixed login bug"));
+
+    let output = db.cli().args(["log", "--notes"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("fixed login bug"));
+    assert!(!stdout.contains("planning"));
+
+    let _ = db.cli().args(["edit", "1", "--note", "reviewed PR"]).output();
+    let output = db.cli()
+        .args(["export", "--format", "json"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"notes\": \"reviewed PR\""));
+
+    let output = db.cli()
+        .args(["export", "--format", "csv"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(",notes"));
+    assert!(stdout.contains("reviewed PR"));
+}
+
+#[test]
+fn test_start_stop_relative_times() {
+    let db = TestDb::new();
+
+    let output = db.cli()
+        .args(["start", "late", "--ago", "2h"])
+        .output()
+        .expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().args(["stop", "--ago", "1h"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Stopped late (1h 0m)"));
+
+    // Starting inside the previous frame is refused
+    let output = db.cli()
+        .args(["start", "overlap", "--ago", "90m"])
+        .output()
+        .expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("overlaps frame 1"));
+
+    // Stopping before the start is refused
+    let _ = db.cli().args(["start", "next", "--ago", "30m"]).output();
+    let output = db.cli().args(["stop", "--ago", "45m"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("before the frame started"));
+}
+
+#[test]
+fn test_start_at_invalid() {
+    let db = TestDb::new();
+
+    let output = db.cli()
+        .args(["start", "proj", "--at", "25:99"])
+        .output()
+        .expect("failed to run");
+    assert!(!output.status.success());
+
+    let output = db.cli()
+        .args(["start", "proj", "--at", "09:00", "--ago", "5m"])
+        .output()
+        .expect("failed to run");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_duration_too_large() {
+    let db = TestDb::new();
+
+    for args in [
+        vec!["start", "x", "--ago", "99999999999d"],
+        vec!["stop", "--ago", "99999999999d"],
+        vec!["budget", "set", "p", "9999999999999999h"],
+    ] {
+        let output = db.cli().args(&args).output().expect("failed to run");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("duration too large"), "{args:?}: {stderr}");
+        assert!(!stderr.contains("panicked"));
+    }
+}
+
+#[test]
+fn test_date_offset_too_large() {
+    let db = TestDb::new();
+
+    for offset in ["-99999999d", "-999999999999999w"] {
+        let output = db.cli().args(["log", "--from", offset]).output().expect("failed to run");
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("offset too large"), "{offset}: {stderr}");
+    }
+}
+
+#[test]
+fn test_relative_date_ranges() {
+    let db = TestDb::new();
+
+    // A frame started now is in every range ending today, whatever the time
+    let _ = db.cli().args(["start", "recent"]).output();
+
+    for args in [
+        vec!["--week"],
+        vec!["--month"],
+        vec!["--from", "-3d"],
+        vec!["--from", "last week"],
+        vec!["--from", "yesterday", "--to", "today"],
+    ] {
+        let output = db.cli().arg("report").args(&args).output().expect("failed to run");
+        assert!(output.status.success(), "{args:?}");
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains("recent"), "{args:?}");
+    }
+
+    // An ISO week in the past has nothing
+    let output = db.cli()
+        .args(["log", "--from", "2020-W01", "--to", "2020-W01"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No frames found"));
+
+    // Shorthands can't be combined with explicit bounds
+    let output = db.cli()
+        .args(["log", "--week", "--from", "today"])
+        .output()
+        .expect("failed to run");
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_check_and_edit_validation() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "first", "--at", "2024-01-01 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-01 11:00"]).output();
+    let _ = db.cli().args(["start", "second", "--at", "2024-01-01 12:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-01 13:00"]).output();
+
+    let output = db.cli().arg("check").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No problems found"));
+
+    // Edits that create problems are refused
+    let output = db.cli()
+        .args(["edit", "2", "--start", "2024-01-01 10:30"])
+        .output()
+        .expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("frame 2 overlaps frame 1"));
+
+    let output = db.cli()
+        .args(["edit", "2", "--end", "2024-01-01 08:00"])
+        .output()
+        .expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("frame 2 ends before it starts"));
+
+    // ...unless forced
+    let output = db.cli()
+        .args(["edit", "2", "--start", "2024-01-01 10:30", "--force"])
+        .output()
+        .expect("failed to run");
+    assert!(output.status.success());
+
+    let output = db.cli().args(["check", "--max-hours", "9223372036854775807"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let output = db.cli().args(["check", "--max-hours", "1"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("frame 1 overlaps frame 2"));
+    assert!(stdout.contains("frame 1 is longer than 1h"));
+
+    // Accept the overlap fix, decline the rest
+    let mut child = db.cli()
+        .args(["check", "--fix"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to run");
+    {
+        use std::io::Write;
+        child.stdin.take().unwrap().write_all(b"y\n").unwrap();
+    }
+    let output = child.wait_with_output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("End frame 1 at 2024-01-01 10:30"));
+    assert!(stdout.contains("Fixed 1 problem(s), 0 left"));
+
+    let output = db.cli().arg("check").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No problems found"));
+
+    // Fixes can be undone
+    let output = db.cli().arg("undo").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Undid fix frame 1"));
+    let output = db.cli().arg("check").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("frame 1 overlaps frame 2"));
+
+    // Frames starting together aren't trimmed to nothing
+    let _ = db.cli().args(["edit", "2", "--start", "2024-01-01 09:00", "--force"]).output();
+    let mut child = db.cli()
+        .args(["check", "--fix"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .expect("failed to run");
+    {
+        use std::io::Write;
+        // check may exit without asking, so the pipe can already be closed
+        let _ = child.stdin.take().unwrap().write_all(b"y\ny\n");
+    }
+    let output = child.wait_with_output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("start at the same time"));
+    assert!(stdout.contains("Fixed 0 problem(s), 1 left"));
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    let empty: i64 = conn
+        .query_row("SELECT COUNT(*) FROM frames WHERE end_time <= start_time", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(empty, 0);
+}
+
+#[test]
+fn test_single_open_frame_enforced() {
+    let db = TestDb::new();
+
+    // A version 3 database with two open frames, as concurrent starts could leave
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    conn.execute_batch(
+        "CREATE TABLE frames (
+            id INTEGER PRIMARY KEY,
+            project TEXT NOT NULL,
+            start_time INTEGER NOT NULL,
+            end_time INTEGER,
+            notes TEXT
+        );
+        CREATE TABLE frame_tags (
+            frame_id INTEGER NOT NULL,
+            tag TEXT NOT NULL,
+            position INTEGER NOT NULL,
+            PRIMARY KEY (frame_id, tag)
+        );
+        INSERT INTO frames (project, start_time) VALUES ('older', strftime('%s','now') - 120);
+        INSERT INTO frames (project, start_time) VALUES ('twin', strftime('%s','now') - 60);
+        INSERT INTO frame_tags VALUES (2, 'dev', 0);
+        INSERT INTO frames (project, start_time) VALUES ('newer', strftime('%s','now') - 60);
+        PRAGMA user_version = 3;",
+    )
+    .unwrap();
+    drop(conn);
+
+    // Migration closes the stray frame and says so; one starting with the
+    // running frame would be empty, so it goes
+    let output = db.cli().arg("status").output().expect("failed to run");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Ended stray open frame 1 (older, started "));
+    assert!(stderr.contains("Deleted stray open frame 2 (twin, started "));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("newer"));
+
+    let output = db.cli().arg("check").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No problems found"));
+
+    // Writers that bypass the CLI can't add a second open frame
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    let result = conn.execute(
+        "INSERT INTO frames (project, start_time) VALUES ('sneaky', strftime('%s','now'))",
+        [],
+    );
+    assert!(result.is_err());
+
+    // Frames from before UUIDs were added get one during migration
+    let missing: i64 = conn
+        .query_row("SELECT COUNT(*) FROM frames WHERE uuid IS NULL OR updated_at IS NULL", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(missing, 0);
+
+    // The deleted stray frame took its tags with it
+    let tags: i64 = conn.query_row("SELECT COUNT(*) FROM frame_tags", [], |row| row.get(0)).unwrap();
+    assert_eq!(tags, 0);
+}
+
+#[test]
+fn test_start_switch() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "first", "+a"]).output();
+
+    let output = db.cli()
+        .args(["start", "second", "+b", "--switch"])
+        .output()
+        .expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Stopped first +a"));
+    assert!(stdout.contains("Started second +b"));
+
+    let output = db.cli().arg("status").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("second +b"));
+
+    // --switch with nothing running just starts
+    let _ = db.cli().arg("stop").output();
+    let output = db.cli()
+        .args(["start", "third", "--switch"])
+        .output()
+        .expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("Stopped"));
+    assert!(stdout.contains("Started third"));
+}
+
+#[test]
+fn test_pause_resume() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "proj", "--ago", "10m"]).output();
+
+    let output = db.cli().arg("resume").output().expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("not paused"));
+
+    let output = db.cli().arg("pause").output().expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Paused proj"));
+
+    let output = db.cli().arg("pause").output().expect("failed to run");
+    assert!(!output.status.success());
+
+    let output = db.cli().arg("status").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("paused for"));
+
+    let output = db.cli().arg("resume").output().expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Resumed proj (paused"));
+
+    // Backdate a second pause so it covers the last five minutes
+    let _ = db.cli().arg("pause").output();
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    conn.execute(
+        "UPDATE frame_breaks SET start_time = strftime('%s','now') - 300 WHERE end_time IS NULL",
+        [],
+    )
+    .unwrap();
+    drop(conn);
+
+    // Can't stop before the pause began
+    let output = db.cli().args(["stop", "--ago", "6m"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("stop time is before the frame was paused"));
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    let bad: i64 = conn
+        .query_row("SELECT COUNT(*) FROM frame_breaks WHERE end_time < start_time", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(bad, 0);
+    drop(conn);
+
+    // Stopping while paused ends the pause; paused time isn't counted
+    let output = db.cli().arg("stop").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Stopped proj (5m"));
+
+    let output = db.cli()
+        .args(["export", "--format", "json"])
+        .output()
+        .expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("\"paused_seconds\": 30"));
+
+    // Ending a paused frame with edit closes its pause like stop does
+    let other = TestDb::new();
+    let _ = other.cli().args(["start", "other", "--at", "2024-02-01 09:00"]).output();
+    let _ = other.cli().arg("pause").output();
+    let conn = rusqlite::Connection::open(&other.path).unwrap();
+    conn.execute(
+        "UPDATE frame_breaks SET start_time = strftime('%s', '2024-02-01 09:30', 'utc') WHERE end_time IS NULL",
+        [],
+    )
+    .unwrap();
+    let id: i64 = conn.query_row("SELECT id FROM frames WHERE end_time IS NULL", [], |row| row.get(0)).unwrap();
+    drop(conn);
+    let output = other.cli().args(["edit", &id.to_string(), "--end", "2024-02-01 10:00"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let conn = rusqlite::Connection::open(&other.path).unwrap();
+    let open: i64 = conn
+        .query_row("SELECT COUNT(*) FROM frame_breaks WHERE end_time IS NULL", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(open, 0);
+    drop(conn);
+    let output = other.cli().args(["log", "--from", "2024-02-01", "--to", "2024-02-01"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("30m"));
+}
+
+#[test]
+fn test_budgets() {
+    let db = TestDb::new();
+
+    let output = db.cli()
+        .args(["budget", "set", "acme", "1h", "--per", "total"])
+        .output()
+        .expect("failed to run");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Budget for acme: 0s / 1h 0m total (0%), 1h 0m left"));
+
+    let _ = db.cli().args(["start", "acme", "--at", "2024-01-01 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-01 09:30"]).output();
+
+    // --all, since the frame isn't in today's report
+    let output = db.cli().args(["report", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Budgets:"));
+    assert!(stdout.contains("acme 30m 0s / 1h 0m total (50%)"));
+
+    // Sub-projects count towards their parent's budget
+    let _ = db.cli().args(["start", "acme/web", "--at", "2024-01-02 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-02 09:10"]).output();
+    let output = db.cli().args(["budget", "list"]).output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stdout).contains("40m 0s / 1h 0m total (66%)"));
+
+    // Going over warns while running and when the frame stops
+    let _ = db.cli().args(["start", "acme", "--ago", "30m"]).output();
+    let output = db.cli().arg("status").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stdout.contains("Budget: 1h 10m / 1h 0m total (116%), over by 10m"));
+    assert!(stderr.contains("Warning: acme is over budget"));
+
+    let output = db.cli().arg("stop").output().expect("failed to run");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("acme is now over budget"));
+
+    let output = db.cli().args(["budget", "list"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("acme"));
+
+    let output = db.cli().args(["budget", "remove", "acme"]).output().expect("failed to run");
+    assert!(output.status.success());
+    let output = db.cli().args(["budget", "list"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("No budgets set"));
+
+    // A period this version doesn't know is an error, not an all-time budget
+    let conn = rusqlite::Connection::open(&db.path).unwrap();
+    conn.execute("INSERT INTO budgets (project, seconds, period) VALUES ('acme', 3600, 'fortnight')", []).unwrap();
+    drop(conn);
+    let output = db.cli().args(["budget", "list"]).output().expect("failed to run");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown period: fortnight"));
+}
+
+#[test]
+fn test_budgets_cover_sub_projects() {
+    let db = TestDb::new();
+    let _ = db.cli().args(["budget", "set", "acme", "30m", "--per", "total"]).output();
+    let _ = db.cli().args(["start", "acme/web", "--ago", "40m"]).output();
+
+    let output = db.cli().arg("status").output().expect("failed to run");
+    // The frame runs on while the test does, so leave out the seconds
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Budget for acme: 40m ") && stdout.contains(" / 30m 0s total (133%)"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Warning: acme is over budget"));
+    let output = db.cli().args(["--format", "json", "status"]).output().expect("failed to run");
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("invalid json");
+    assert_eq!(json["budget"]["project"], "acme");
+    assert_eq!(json["budget"]["over"], true);
+
+    let output = db.cli().arg("stop").output().expect("failed to run");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("acme is now over budget"));
+    let output = db.cli().args(["report", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Budgets:") && stdout.contains("  acme 40m "));
+    assert!(stdout.contains(" / 30m 0s total (133%)"));
+}
+
+#[test]
+fn test_log_newest_first() {
+    let db = TestDb::new();
+
+    let _ = db.cli().args(["start", "older", "--at", "2024-01-01 09:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-01 10:00"]).output();
+    let _ = db.cli().args(["start", "newer", "--at", "2024-01-01 11:00"]).output();
+    let _ = db.cli().args(["stop", "--at", "2024-01-01 12:00"]).output();
+
+    let output = db.cli().args(["log", "--all"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.find("newer").unwrap() < stdout.find("older").unwrap());
+
+    // Export is chronological
+    let output = db.cli().args(["export"]).output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.find("older").unwrap() < stdout.find("newer").unwrap());
+
+    // Restart picks the frame that ended last
+    let output = db.cli().arg("restart").output().expect("failed to run");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Started newer"));
+}